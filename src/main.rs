@@ -1,11 +1,21 @@
 mod modules;
+mod scenes;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use env_logger::Env;
 use modules::app_manager::AppManager;
+use modules::control_socket;
+use modules::mqtt_handler::AppCommand;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("msg") {
+        // Initialize logging
+        env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+        return run_msg_command(args.collect()).await;
+    }
+
     // Initialize logging
     env_logger::Builder::from_env(Env::default().default_filter_or("info"))
         .format_timestamp_millis()
@@ -22,32 +32,48 @@ async fn main() -> Result<()> {
 
     log::info!("Connecting to MQTT broker {}:{}", mqtt_broker, mqtt_port);
 
-    // Create and run the application manager
-    let mut app_manager = AppManager::new(&mqtt_broker, mqtt_port)?;
-
-    // Handle Ctrl+C gracefully
-    let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
-
-    tokio::spawn(async move {
-        if let Err(e) = tokio::signal::ctrl_c().await {
-            log::error!("Failed to listen for Ctrl+C: {}", e);
-            return;
-        }
-        let _ = shutdown_tx.send(());
-    });
-
-    // Run until shutdown signal
-    tokio::select! {
-        result = app_manager.run() => {
-            if let Err(e) = result {
-                log::error!("Application manager error: {}", e);
-            }
-        }
-        _ = &mut shutdown_rx => {
-            log::info!("Shutdown signal received");
-        }
+    let heartbeat_interval = std::env::var("MQTT_HEARTBEAT_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(std::time::Duration::from_secs(5));
+    let heartbeat_topic =
+        std::env::var("MQTT_HEARTBEAT_TOPIC").unwrap_or_else(|_| "app/heartbeat".to_string());
+
+    // Create and run the application manager. `AppManager::run` owns signal
+    // handling end-to-end (Ctrl+C/SIGTERM trigger its own graceful shutdown:
+    // flushing MQTT, killing running apps, etc.), so there's no separate
+    // listener here racing it for the same signal.
+    let mut app_manager =
+        AppManager::new(&mqtt_broker, mqtt_port, heartbeat_interval, &heartbeat_topic)?;
+
+    if let Err(e) = app_manager.run().await {
+        log::error!("Application manager error: {}", e);
     }
 
     log::info!("Protosuit engine client shutting down");
     Ok(())
+}
+
+/// Handles `protosuit-engine msg <switch|start|stop> <name>`: connects to the
+/// running engine's control socket and sends a single `AppCommand`, the same
+/// way an MQTT publish to `app/switch`/`app/start`/`app/stop` would.
+async fn run_msg_command(args: Vec<String>) -> Result<()> {
+    let mut args = args.into_iter();
+    let subcommand = args.next().context(
+        "Usage: protosuit-engine msg <switch|start|stop> <name>",
+    )?;
+    let name = args
+        .next()
+        .with_context(|| format!("Usage: protosuit-engine msg {} <name>", subcommand))?;
+
+    let command = match subcommand.as_str() {
+        "switch" => AppCommand::Switch { name },
+        "start" => AppCommand::Start { name },
+        "stop" => AppCommand::Stop { name },
+        other => anyhow::bail!("Unknown msg subcommand '{}' (expected switch, start, or stop)", other),
+    };
+
+    control_socket::send(&command).await?;
+    Ok(())
 }
\ No newline at end of file
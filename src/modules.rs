@@ -1,11 +1,20 @@
 pub mod sdl_manager;
 pub mod mqtt_handler;
+pub mod mpris_handler;
 pub mod window_manager;
+pub mod app_registry;
+pub mod display_stream;
+pub mod ambient_light;
+pub mod control_socket;
 pub mod app_manager;
 pub mod idle_display;
 
 pub use sdl_manager::SDLManager;
 pub use mqtt_handler::MQTTHandler;
-pub use window_manager::WindowManager;
+pub use mpris_handler::{MPRISHandler, NowPlaying};
+pub use window_manager::{Output, ResizeEvent, WindowManager, WindowState};
+pub use app_registry::AppRegistry;
+pub use display_stream::{FrameReceiver, FrameSender};
+pub use ambient_light::AmbientLight;
 pub use app_manager::AppManager;
 pub use idle_display::IdleDisplay;
@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use serde::Serialize;
+use serialport::SerialPort;
+use std::io::Write;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Adalight frame preamble, per the protocol this firmware expects.
+const ADALIGHT_MAGIC: [u8; 3] = *b"Ada";
+
+/// Pixel thickness of the border strip sampled along each edge. Kept small
+/// since `read_pixels` cost scales with the region size.
+const STRIP_THICKNESS: u32 = 4;
+
+/// How many LED zones ring each edge of the suit - every physical build
+/// wires up a different LED count, so each edge is independently
+/// configurable via env vars.
+#[derive(Debug, Clone, Copy)]
+pub struct ZoneCounts {
+    pub top: usize,
+    pub bottom: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl ZoneCounts {
+    pub fn from_env() -> Self {
+        let zones = |var: &str, default: usize| {
+            std::env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            top: zones("PROTOSUIT_LED_ZONES_TOP", 8),
+            bottom: zones("PROTOSUIT_LED_ZONES_BOTTOM", 8),
+            left: zones("PROTOSUIT_LED_ZONES_LEFT", 6),
+            right: zones("PROTOSUIT_LED_ZONES_RIGHT", 6),
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.top + self.bottom + self.left + self.right
+    }
+}
+
+/// Compact mirror of the Adalight frame published to MQTT for anything that
+/// wants the ambient colors without speaking the serial protocol.
+#[derive(Debug, Serialize)]
+pub struct LedFrame {
+    pub colors: Vec<(u8, u8, u8)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Holds the most recently captured frame for the serial writer thread to
+/// pick up. A plain slot rather than a queue, since a frame the writer
+/// hasn't gotten to yet is stale the moment a newer capture replaces it -
+/// only the latest colors matter for ambient lighting.
+#[derive(Default)]
+struct PendingFrame {
+    slot: Mutex<Option<Vec<(u8, u8, u8)>>>,
+    condvar: Condvar,
+}
+
+impl PendingFrame {
+    fn replace(&self, colors: Vec<(u8, u8, u8)>) {
+        *self.slot.lock().unwrap() = Some(colors);
+        self.condvar.notify_one();
+    }
+
+    fn take_blocking(&self) -> Vec<(u8, u8, u8)> {
+        let mut slot = self.slot.lock().unwrap();
+        loop {
+            if let Some(colors) = slot.take() {
+                return colors;
+            }
+            slot = self.condvar.wait(slot).unwrap();
+        }
+    }
+}
+
+/// Samples a rendered canvas's border pixels and drives a WS2812/Adalight LED
+/// strip over serial, mirroring the same colors to MQTT via `led_tx`.
+/// `read_pixels` is too expensive to call every render tick, so captures are
+/// rate-limited independently of (and below) the render rate. The serial
+/// write itself (up to the port's 100ms timeout) runs on a dedicated thread
+/// fed by `pending_frame`, so a stalling LED controller can't block
+/// `capture` - and with it every window's render tick - on the shared SDL
+/// thread.
+pub struct AmbientLight {
+    zones: ZoneCounts,
+    capture_interval: Duration,
+    last_capture: Option<Instant>,
+    led_tx: mpsc::UnboundedSender<Vec<(u8, u8, u8)>>,
+    pending_frame: Arc<PendingFrame>,
+}
+
+impl AmbientLight {
+    pub fn open(
+        port_path: &str,
+        baud_rate: u32,
+        zones: ZoneCounts,
+        capture_fps: f64,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<Vec<(u8, u8, u8)>>)> {
+        let port = serialport::new(port_path, baud_rate)
+            .timeout(Duration::from_millis(100))
+            .open()
+            .with_context(|| format!("Failed to open LED serial port {}", port_path))?;
+
+        let (led_tx, led_rx) = mpsc::unbounded_channel();
+        let pending_frame = Arc::new(PendingFrame::default());
+        Self::spawn_serial_writer(port, pending_frame.clone())?;
+
+        Ok((
+            Self {
+                zones,
+                capture_interval: Duration::from_secs_f64(1.0 / capture_fps),
+                last_capture: None,
+                led_tx,
+                pending_frame,
+            },
+            led_rx,
+        ))
+    }
+
+    /// Owns the serial port for the life of the program, writing the latest
+    /// frame handed to it each time one's available. Runs off the SDL thread
+    /// so a hung or slow LED controller only delays this thread, not
+    /// rendering.
+    fn spawn_serial_writer(mut port: Box<dyn SerialPort>, pending_frame: Arc<PendingFrame>) -> Result<()> {
+        thread::Builder::new()
+            .name("ambient-light-serial".to_string())
+            .spawn(move || loop {
+                let colors = pending_frame.take_blocking();
+                if let Err(e) = Self::write_adalight_frame(port.as_mut(), &colors) {
+                    log::warn!("Failed to write Adalight frame: {}", e);
+                }
+            })
+            .context("Failed to spawn ambient light serial thread")?;
+        Ok(())
+    }
+
+    /// Runs on the SDL thread inside a scene's render closure, after
+    /// `canvas.present()`. No-op if called again before `capture_interval`
+    /// has elapsed since the last capture.
+    pub fn capture(&mut self, canvas: &mut Canvas<Window>) -> Result<()> {
+        if let Some(last) = self.last_capture {
+            if last.elapsed() < self.capture_interval {
+                return Ok(());
+            }
+        }
+        self.last_capture = Some(Instant::now());
+
+        let (width, height) = canvas
+            .output_size()
+            .map_err(|e| anyhow::anyhow!("Failed to get canvas size: {}", e))?;
+
+        let mut colors = Vec::with_capacity(self.zones.total());
+        colors.extend(self.sample_edge(canvas, width, height, Edge::Top)?);
+        colors.extend(self.sample_edge(canvas, width, height, Edge::Right)?);
+        colors.extend(self.sample_edge(canvas, width, height, Edge::Bottom)?);
+        colors.extend(self.sample_edge(canvas, width, height, Edge::Left)?);
+
+        self.pending_frame.replace(colors.clone());
+        let _ = self.led_tx.send(colors);
+        Ok(())
+    }
+
+    fn sample_edge(
+        &self,
+        canvas: &mut Canvas<Window>,
+        width: u32,
+        height: u32,
+        edge: Edge,
+    ) -> Result<Vec<(u8, u8, u8)>> {
+        let zone_count = match edge {
+            Edge::Top => self.zones.top,
+            Edge::Bottom => self.zones.bottom,
+            Edge::Left => self.zones.left,
+            Edge::Right => self.zones.right,
+        };
+        if zone_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let thickness = STRIP_THICKNESS.min(width).min(height);
+        let rect = match edge {
+            Edge::Top => Rect::new(0, 0, width, thickness),
+            Edge::Bottom => Rect::new(0, (height - thickness) as i32, width, thickness),
+            Edge::Left => Rect::new(0, 0, thickness, height),
+            Edge::Right => Rect::new((width - thickness) as i32, 0, thickness, height),
+        };
+
+        let rgb = canvas
+            .read_pixels(rect, PixelFormatEnum::RGB24)
+            .map_err(|e| anyhow::anyhow!("Failed to read {:?} edge pixels: {}", edge, e))?;
+
+        let (strip_w, strip_h) = (rect.width() as usize, rect.height() as usize);
+        let is_horizontal = matches!(edge, Edge::Top | Edge::Bottom);
+        let span = if is_horizontal { strip_w } else { strip_h };
+
+        let mut colors = Vec::with_capacity(zone_count);
+        for zone in 0..zone_count {
+            let start = zone * span / zone_count;
+            let end = ((zone + 1) * span / zone_count).max(start + 1);
+            colors.push(average_zone(&rgb, strip_w, strip_h, is_horizontal, start, end));
+        }
+        Ok(colors)
+    }
+
+    /// `"Ada"` magic + big-endian LED-count-minus-1 + XOR checksum + gamma
+    /// corrected RGB triples, per the Adalight serial protocol. Runs on the
+    /// serial writer thread, not the SDL thread.
+    fn write_adalight_frame(port: &mut dyn SerialPort, colors: &[(u8, u8, u8)]) -> Result<()> {
+        let led_count_minus_one = colors.len().saturating_sub(1) as u16;
+        let hi = (led_count_minus_one >> 8) as u8;
+        let lo = (led_count_minus_one & 0xff) as u8;
+        let checksum = hi ^ lo ^ 0x55;
+
+        let mut frame = Vec::with_capacity(6 + colors.len() * 3);
+        frame.extend_from_slice(&ADALIGHT_MAGIC);
+        frame.extend_from_slice(&[hi, lo, checksum]);
+        for &(r, g, b) in colors {
+            frame.extend_from_slice(&[gamma_correct(r), gamma_correct(g), gamma_correct(b)]);
+        }
+
+        port.write_all(&frame)
+            .context("Failed to write Adalight frame to serial port")?;
+        Ok(())
+    }
+}
+
+fn average_zone(rgb: &[u8], width: usize, height: usize, is_horizontal: bool, start: usize, end: usize) -> (u8, u8, u8) {
+    let (mut r, mut g, mut b, mut count) = (0u64, 0u64, 0u64, 0u64);
+
+    if is_horizontal {
+        for y in 0..height {
+            for x in start..end.min(width) {
+                let i = (y * width + x) * 3;
+                r += rgb[i] as u64;
+                g += rgb[i + 1] as u64;
+                b += rgb[i + 2] as u64;
+                count += 1;
+            }
+        }
+    } else {
+        for y in start..end.min(height) {
+            for x in 0..width {
+                let i = (y * width + x) * 3;
+                r += rgb[i] as u64;
+                g += rgb[i + 1] as u64;
+                b += rgb[i + 2] as u64;
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        return (0, 0, 0);
+    }
+    ((r / count) as u8, (g / count) as u8, (b / count) as u8)
+}
+
+/// `out = (in / 255)^2.2 * 255`, applied per channel so the strip's
+/// perceived brightness matches what's rendered on screen.
+fn gamma_correct(value: u8) -> u8 {
+    let normalized = value as f64 / 255.0;
+    (normalized.powf(2.2) * 255.0).round() as u8
+}
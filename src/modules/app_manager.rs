@@ -1,32 +1,52 @@
 use anyhow::{Context, Result};
+use futures_util::stream::StreamExt;
+use signal_hook::consts::signal::{SIGHUP, SIGTERM};
+use signal_hook_tokio::Signals;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
 use hostname;
 use crate::modules::{
+    ambient_light::{AmbientLight, LedFrame, ZoneCounts},
+    app_registry::AppRegistry,
+    control_socket,
+    display_stream::FrameSender,
+    mpris_handler::MPRISHandler,
     mqtt_handler::{AppCommand, MQTTHandler},
     sdl_manager::SDLManager,
-    window_manager::WindowManager,
-};
-use crate::scenes::{
-    debug_scene::DebugScene,
-    idle_scene::IdleScene,
+    window_manager::{ResizeEvent, WindowManager},
 };
+use crate::scenes::{DebugScene, IdleScene, Scene, SceneRegistry};
 
 pub struct AppManager {
     sdl_manager: Arc<SDLManager>,
     window_manager: Arc<WindowManager>,
     mqtt_handler: Option<MQTTHandler>,
+    command_tx: mpsc::Sender<AppCommand>,
     command_rx: mpsc::Receiver<AppCommand>,
     mqtt_status_rx: mpsc::Receiver<bool>,
+    sdl_quit_rx: mpsc::UnboundedReceiver<()>,
+    resize_rx: mpsc::UnboundedReceiver<ResizeEvent>,
+    led_rx: Option<mpsc::UnboundedReceiver<Vec<(u8, u8, u8)>>>,
+    led_topic: String,
+    ambient_light: Option<Arc<std::sync::Mutex<AmbientLight>>>,
+    app_registry: Arc<AppRegistry>,
+    scene_registry: SceneRegistry,
+    scenes: HashMap<String, Box<dyn Scene>>,
     active_scene: String, // Track which scene is currently active
-    debug_scene: Option<DebugScene>,
-    idle_scene: Option<IdleScene>,
+    output_refresh_rate: Option<f64>,
 }
 
 impl AppManager {
-    pub fn new(mqtt_broker: &str, mqtt_port: u16) -> Result<Self> {
-        let sdl_manager = Arc::new(SDLManager::new()?);
+    pub fn new(
+        mqtt_broker: &str,
+        mqtt_port: u16,
+        heartbeat_interval: Duration,
+        heartbeat_topic: &str,
+    ) -> Result<Self> {
+        let (sdl_quit_tx, sdl_quit_rx) = mpsc::unbounded_channel();
+        let sdl_manager = Arc::new(SDLManager::new(sdl_quit_tx)?);
         let window_manager = Arc::new(WindowManager::new()?);
 
         let (command_tx, command_rx) = mpsc::channel(32);
@@ -36,10 +56,28 @@ impl AppManager {
             mqtt_broker,
             mqtt_port,
             &format!("protosuit-engine-client-{}", hostname::get()?.to_string_lossy()),
-            command_tx,
+            command_tx.clone(),
             mqtt_status_tx,
+            sdl_manager.clone(),
+            heartbeat_interval,
+            heartbeat_topic.to_string(),
         )?;
 
+        // Poll MPRIS over the session bus on its own task; the idle scene's
+        // factory below just reads whatever the watch channel last saw.
+        let now_playing_rx = MPRISHandler::spawn();
+
+        // Every known scene gets one registry entry: a window title and a
+        // constructor closure. Adding a scene means adding an entry here -
+        // `handle_switch` and the render tick don't know scene names at all.
+        let mut scene_registry = SceneRegistry::new();
+        scene_registry.register("debug", "Protosuit Debug", |sdl, window_id, stream_sender, ambient_light| {
+            Ok(Box::new(DebugScene::new(sdl, window_id, stream_sender, ambient_light)?) as Box<dyn Scene>)
+        });
+        scene_registry.register("idle", "Protosuit Idle", move |sdl, window_id, stream_sender, ambient_light| {
+            Ok(Box::new(IdleScene::new(sdl, window_id, now_playing_rx.clone(), stream_sender, ambient_light)?) as Box<dyn Scene>)
+        });
+
         // Get default scene from environment variable, fallback to "debug"
         let mut default_scene = std::env::var("PROTOSUIT_ENGINE_DEFAULT_SCENE")
             .unwrap_or_else(|_| {
@@ -48,43 +86,109 @@ impl AppManager {
             });
 
         // Validate the scene name
-        if !matches!(default_scene.as_str(), "debug" | "idle") {
+        if !scene_registry.contains(&default_scene) {
             log::warn!("Unknown default scene '{}', falling back to debug", default_scene);
             default_scene = "debug".to_string();
         }
 
         log::info!("Loading default scene: {}", default_scene);
 
-        // Initialize scenes as None
-        let mut debug_scene = None;
-        let mut idle_scene = None;
-
-        // Create the default scene
-        match default_scene.as_str() {
-            "debug" => {
-                log::debug!("Creating debug scene");
-                let debug_canvas = (*sdl_manager).launch_app("Protosuit Debug", "true", &[])?
-                    .context("Failed to get debug canvas")?;
-                debug_scene = Some(DebugScene::new(debug_canvas)?);
+        let manifest_path = std::env::var("PROTOSUIT_APPS").unwrap_or_else(|_| "apps.toml".to_string());
+        log::info!("Loading app manifest from {}", manifest_path);
+        let app_registry = Arc::new(AppRegistry::load(manifest_path)?);
+
+        // Ambient LED output is opt-in: only set up the serial port (and the
+        // per-scene capture hook) when PROTOSUIT_LED_SERIAL_PORT is set.
+        let (ambient_light, led_rx) = match std::env::var("PROTOSUIT_LED_SERIAL_PORT") {
+            Ok(port_path) => {
+                let baud_rate = std::env::var("PROTOSUIT_LED_BAUD_RATE")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(115_200);
+                let capture_fps = std::env::var("PROTOSUIT_LED_CAPTURE_FPS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10.0);
+                let zones = ZoneCounts::from_env();
+
+                match AmbientLight::open(&port_path, baud_rate, zones, capture_fps) {
+                    Ok((ambient_light, led_rx)) => (Some(Arc::new(std::sync::Mutex::new(ambient_light))), Some(led_rx)),
+                    Err(e) => {
+                        log::error!("Failed to open LED serial port {}: {}", port_path, e);
+                        (None, None)
+                    }
+                }
             }
-            "idle" => {
-                log::debug!("Creating idle scene");
-                let idle_canvas = (*sdl_manager).launch_app("Protosuit Idle", "true", &[])?
-                    .context("Failed to get idle canvas")?;
-                idle_scene = Some(IdleScene::new(idle_canvas)?);
+            Err(_) => (None, None),
+        };
+        let led_topic = std::env::var("PROTOSUIT_LED_MQTT_TOPIC").unwrap_or_else(|_| "app/leds".to_string());
+
+        // Create the default scene through the same path `handle_switch` uses.
+        let (default_window_id, default_scene_wants_local, default_scene_instance) = Self::construct_scene(
+            &scene_registry,
+            &sdl_manager,
+            &app_registry,
+            &ambient_light,
+            &default_scene,
+        )?;
+        let mut scenes: HashMap<String, Box<dyn Scene>> = HashMap::new();
+        scenes.insert(default_scene.clone(), default_scene_instance);
+
+        // A fursuit display runs fullscreen by default; set PROTOSUIT_ENGINE_FULLSCREEN=0
+        // to run windowed during development. None of this applies if the
+        // default scene's manifest entry is `transport = "stream"` - its
+        // window is hidden and `WindowManager` never touches it.
+        let fullscreen = std::env::var("PROTOSUIT_ENGINE_FULLSCREEN")
+            .map(|v| v != "0" && v.to_lowercase() != "false")
+            .unwrap_or(true);
+        if default_scene_wants_local {
+            if fullscreen {
+                window_manager.set_fullscreen(default_window_id, true)?;
             }
-            _ => unreachable!(), // We validated the scene name above
+            window_manager.track_resize(default_window_id)?;
         }
+        let resize_rx = window_manager.spawn_resize_watcher();
+
+        // A protogen head typically exposes two eye panels as separate RandR
+        // outputs; PROTOSUIT_ENGINE_OUTPUT pins the single active window to
+        // one of them instead of wherever the compositor placed it, and lets
+        // that output's own refresh rate drive the render tick. There's no
+        // per-scene output assignment and only one scene renders per tick -
+        // driving an independent scene per eye is future work, not something
+        // this variable does today.
+        let output_refresh_rate = match (std::env::var("PROTOSUIT_ENGINE_OUTPUT"), default_scene_wants_local) {
+            (Ok(output_name), true) => {
+                match window_manager.outputs()?.into_iter().find(|o| o.name == output_name) {
+                    Some(output) => {
+                        window_manager.position_window(default_window_id, output.x, output.y)?;
+                        (output.refresh_rate > 0.0).then_some(output.refresh_rate)
+                    }
+                    None => {
+                        log::warn!("PROTOSUIT_ENGINE_OUTPUT '{}' not found among RandR outputs", output_name);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
 
         Ok(Self {
             sdl_manager,
             window_manager,
             mqtt_handler: Some(mqtt_handler),
+            command_tx,
             command_rx,
             mqtt_status_rx,
+            sdl_quit_rx,
+            resize_rx,
+            led_rx,
+            led_topic,
+            ambient_light,
+            app_registry,
+            scene_registry,
+            scenes,
             active_scene: default_scene,
-            debug_scene,
-            idle_scene,
+            output_refresh_rate,
         })
     }
 
@@ -98,6 +202,32 @@ impl AppManager {
         // Replace the default shutdown receiver with our own
         mqtt_handler.shutdown_rx = mqtt_shutdown_rx;
 
+        // Mirror ambient light frames to MQTT on their own task - the capture
+        // itself happens synchronously on the SDL thread, so it can't publish
+        // through the async client directly.
+        if let Some(mut led_rx) = self.led_rx.take() {
+            let led_client = mqtt_handler.client();
+            let led_topic = self.led_topic.clone();
+            tokio::spawn(async move {
+                while let Some(colors) = led_rx.recv().await {
+                    let frame = LedFrame { colors };
+                    match serde_json::to_vec(&frame) {
+                        Ok(body) => {
+                            if let Err(e) = led_client.publish(&led_topic, rumqttc::QoS::AtMostOnce, false, body).await {
+                                log::warn!("Failed to publish ambient light frame: {}", e);
+                            }
+                        }
+                        Err(e) => log::warn!("Failed to serialize ambient light frame: {}", e),
+                    }
+                }
+            });
+        }
+
+        // Local control socket: lets `protosuit-engine msg` drive Start/Stop/
+        // Switch the same way an MQTT publish would, without needing a
+        // broker - handy for scripting and keybind integration.
+        let control_socket_handle = control_socket::spawn(self.command_tx.clone())?;
+
         // Spawn MQTT handler task
         let mqtt_handle = tokio::spawn(async move {
             if let Err(e) = mqtt_handler.start().await {
@@ -105,25 +235,50 @@ impl AppManager {
             }
         });
 
-        // Create an interval for updating displays
-        let mut update_interval = interval(Duration::from_secs(1));
+        // An animated face needs real frame pacing, not a once-a-second
+        // redraw - PROTOSUIT_ENGINE_FPS controls the render tick rate, unless
+        // PROTOSUIT_ENGINE_OUTPUT pinned us to an output with a known refresh
+        // rate, in which case that takes precedence.
+        let fps: f64 = self.output_refresh_rate.unwrap_or_else(|| {
+            std::env::var("PROTOSUIT_ENGINE_FPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30.0)
+        });
+        let mut render_interval = interval(Duration::from_secs_f64(1.0 / fps));
 
-        // Handle Ctrl+C and SIGTERM
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+        // Handle Ctrl+C
+        let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
 
+        let ctrl_c_shutdown_tx = shutdown_tx.clone();
         tokio::spawn(async move {
-            let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
-                .expect("Failed to create SIGTERM signal handler");
+            if tokio::signal::ctrl_c().await.is_ok() {
+                log::info!("Received Ctrl+C signal");
+            }
+            let _ = ctrl_c_shutdown_tx.send(());
+        });
 
-            tokio::select! {
-                _ = tokio::signal::ctrl_c() => {
-                    log::info!("Received Ctrl+C signal");
-                }
-                _ = sigterm.recv() => {
-                    log::info!("Received SIGTERM signal");
+        // SIGHUP reloads the app manifest in place; SIGTERM joins the same
+        // graceful shutdown path as Ctrl+C.
+        let mut signals = Signals::new([SIGHUP, SIGTERM]).context("Failed to register signal handler")?;
+        let signal_registry = self.app_registry.clone();
+        let signal_shutdown_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            while let Some(signal) = signals.next().await {
+                match signal {
+                    SIGHUP => {
+                        log::info!("Received SIGHUP, reloading app manifest");
+                        if let Err(e) = signal_registry.reload() {
+                            log::error!("Failed to reload app manifest: {}", e);
+                        }
+                    }
+                    SIGTERM => {
+                        log::info!("Received SIGTERM signal");
+                        let _ = signal_shutdown_tx.send(());
+                    }
+                    _ => unreachable!(),
                 }
             }
-            let _ = shutdown_tx.send(());
         });
 
         let result = loop {
@@ -133,8 +288,8 @@ impl AppManager {
             tokio::select! {
                 Some(command) = self.command_rx.recv() => {
                     match command {
-                        AppCommand::Start { name, command, args } => {
-                            if let Err(e) = self.handle_start(&name, &command, &args).await {
+                        AppCommand::Start { name } => {
+                            if let Err(e) = self.handle_start(&name).await {
                                 break Err(e);
                             }
                         }
@@ -152,47 +307,40 @@ impl AppManager {
                 }
                 Some(mqtt_connected) = self.mqtt_status_rx.recv() => {
                     log::debug!("MQTT connection status changed to: {}", mqtt_connected);
-                    if let Some(debug_scene) = &mut self.debug_scene {
-                        debug_scene.set_mqtt_status(mqtt_connected);
+                    for scene in self.scenes.values_mut() {
+                        scene.set_mqtt_status(mqtt_connected);
                     }
                 }
-                _ = update_interval.tick() => {
-                    match self.active_scene.as_str() {
-                        "debug" => {
-                            if let Some(debug_scene) = &mut self.debug_scene {
-                                if let Err(e) = debug_scene.render() {
-                                    log::error!("Failed to render debug scene: {}", e);
-                                }
-                            }
-                        }
-                        "idle" => {
-                            if let Some(idle_scene) = &mut self.idle_scene {
-                                if let Err(e) = idle_scene.render() {
-                                    log::error!("Failed to render idle scene: {}", e);
-                                }
+                _ = render_interval.tick() => {
+                    match self.scenes.get_mut(&self.active_scene) {
+                        Some(scene) => {
+                            if let Err(e) = scene.render() {
+                                log::error!("Failed to render {} scene: {}", self.active_scene, e);
                             }
                         }
-                        _ => {
-                            log::error!("Unknown active scene: {}", self.active_scene);
-                        }
+                        None => log::error!("Unknown active scene: {}", self.active_scene),
                     }
                 }
-                _ = &mut shutdown_rx => {
+                Some(()) = self.sdl_quit_rx.recv() => {
+                    log::info!("SDL quit event received, triggering shutdown");
+                    let _ = shutdown_tx.send(());
+                }
+                Some(event) = self.resize_rx.recv() => {
+                    self.handle_resize(event);
+                }
+                Some(()) = shutdown_rx.recv() => {
                     log::info!("Shutdown signal received, cleaning up...");
                     // Send shutdown signal to MQTT handler
                     let _ = mqtt_shutdown_tx.send(());
                     // Wait for MQTT handler to finish
                     let _ = mqtt_handle.await;
+                    control_socket_handle.abort();
                     // Stop all running apps
                     if let Err(e) = self.cleanup().await {
                         break Err(e);
                     }
                     break Ok(());
                 }
-                // Add frame delay with synchronous sleep
-                _ = tokio::task::spawn_blocking(|| {
-                    std::thread::sleep(std::time::Duration::from_millis(10));
-                }) => {}
             }
         };
 
@@ -200,12 +348,80 @@ impl AppManager {
         result
     }
 
-    async fn handle_start(&mut self, name: &str, command: &str, args: &[String]) -> Result<()> {
-        // Convert args to &str slice
-        let args_str: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+    /// Builds a `FrameSender` for `name` if the manifest opts it into
+    /// streaming. Only scenes that own their own canvas (debug/idle) can
+    /// actually produce pixels to stream.
+    fn make_stream_sender(app_registry: &AppRegistry, name: &str) -> Option<Arc<std::sync::Mutex<FrameSender>>> {
+        let entry = app_registry.get(name)?;
+        if !entry.transport.wants_stream() {
+            return None;
+        }
+
+        let target = entry.stream_target.as_deref()?;
+        let addr = match target.parse() {
+            Ok(addr) => addr,
+            Err(e) => {
+                log::error!("Invalid stream_target '{}' for '{}': {}", target, name, e);
+                return None;
+            }
+        };
+
+        match FrameSender::new(addr) {
+            Ok(sender) => Some(Arc::new(std::sync::Mutex::new(sender))),
+            Err(e) => {
+                log::error!("Failed to start frame stream sender for '{}': {}", name, e);
+                None
+            }
+        }
+    }
+
+    /// Dispatches a resize observed on the X11 event stream to whichever
+    /// scene owns that window, so it can recompute layout instead of
+    /// querying `output_size()` every render.
+    fn handle_resize(&mut self, event: ResizeEvent) {
+        for scene in self.scenes.values_mut() {
+            if scene.window_id() == event.window_id {
+                scene.on_resize(event.width, event.height);
+                return;
+            }
+        }
+    }
+
+    /// Launches `name`'s window and constructs it through the scene
+    /// registry. Every scene gets a window - it's the canvas it renders (and
+    /// streams) onto - but a `transport = "stream"` manifest entry gets a
+    /// hidden one that `WindowManager` never touches, instead of a real
+    /// on-screen window. Shared by `new` (for the default scene) and
+    /// `handle_switch` (for any scene switched to lazily) so there's one
+    /// place that knows how a scene comes into existence.
+    fn construct_scene(
+        scene_registry: &SceneRegistry,
+        sdl_manager: &Arc<SDLManager>,
+        app_registry: &AppRegistry,
+        ambient_light: &Option<Arc<std::sync::Mutex<AmbientLight>>>,
+        name: &str,
+    ) -> Result<(u32, bool, Box<dyn Scene>)> {
+        log::debug!("Creating {} scene", name);
+        let wants_local = app_registry.get(name).map(|entry| entry.transport.wants_local()).unwrap_or(true);
+        let title = scene_registry.title(name).map(str::to_string).unwrap_or_else(|| name.to_string());
+        let window_id = sdl_manager.launch_app(&title, "true", &[], None, wants_local)?;
+        let stream_sender = Self::make_stream_sender(app_registry, name);
+        let scene = scene_registry.construct(name, sdl_manager.clone(), window_id, stream_sender, ambient_light.clone())?;
+        Ok((window_id, wants_local, scene))
+    }
+
+    async fn handle_start(&mut self, name: &str) -> Result<()> {
+        let entry = self.app_registry.get(name)
+            .with_context(|| format!("No app named '{}' in the manifest", name))?;
+        let args_str: Vec<&str> = entry.args.iter().map(AsRef::as_ref).collect();
 
-        // Launch the application
-        self.sdl_manager.launch_app(name, command, &args_str)?;
+        // Launch the application. A stream-only manifest entry still runs
+        // the process, it just never gets a local SDL window.
+        if entry.transport.wants_local() {
+            self.sdl_manager.launch_app(name, &entry.command, &args_str, entry.window_size, true)?;
+        } else {
+            self.sdl_manager.spawn_app(name, &entry.command, &args_str)?;
+        }
 
         // If this is the first app, make it active
         if self.active_scene.is_empty() {
@@ -236,30 +452,24 @@ impl AppManager {
     }
 
     async fn handle_switch(&mut self, name: &str) -> Result<()> {
-        match name {
-            "debug" => {
-                if self.debug_scene.is_none() {
-                    log::debug!("Creating debug scene");
-                    let debug_canvas = self.sdl_manager.launch_app("Protosuit Debug", "true", &[])?
-                        .context("Failed to get debug canvas")?;
-                    self.debug_scene = Some(DebugScene::new(debug_canvas)?);
-                }
-                self.active_scene = "debug".to_string();
-            }
-            "idle" => {
-                if self.idle_scene.is_none() {
-                    log::debug!("Creating idle scene");
-                    let idle_canvas = self.sdl_manager.launch_app("Protosuit Idle", "true", &[])?
-                        .context("Failed to get idle canvas")?;
-                    self.idle_scene = Some(IdleScene::new(idle_canvas)?);
-                }
-                self.active_scene = "idle".to_string();
-            }
-            _ => {
+        if !self.scenes.contains_key(name) {
+            if !self.scene_registry.contains(name) {
                 log::error!("Unknown scene: {}", name);
                 return Ok(());
             }
+            let (window_id, wants_local, scene) = Self::construct_scene(
+                &self.scene_registry,
+                &self.sdl_manager,
+                &self.app_registry,
+                &self.ambient_light,
+                name,
+            )?;
+            if wants_local {
+                self.window_manager.track_resize(window_id)?;
+            }
+            self.scenes.insert(name.to_string(), scene);
         }
+        self.active_scene = name.to_string();
         Ok(())
     }
 
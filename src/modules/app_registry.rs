@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Where a rendered scene's frames go: the local SDL window, a remote display
+/// node over the chunked UDP protocol in `display_stream`, or both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    #[default]
+    Local,
+    Stream,
+    Both,
+}
+
+impl Transport {
+    pub fn wants_local(self) -> bool {
+        matches!(self, Transport::Local | Transport::Both)
+    }
+
+    pub fn wants_stream(self) -> bool {
+        matches!(self, Transport::Stream | Transport::Both)
+    }
+}
+
+/// One entry from the TOML app manifest: how to launch a logical app name
+/// without the controller having to know the launch line itself.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppManifestEntry {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub window_size: Option<(u32, u32)>,
+    #[serde(default)]
+    pub transport: Transport,
+    /// Required when `transport` is `stream` or `both` - `host:port` of the
+    /// remote display node.
+    pub stream_target: Option<String>,
+}
+
+/// The manifest is just a flat table of `name -> entry`, e.g.:
+///
+/// ```toml
+/// [idle]
+/// command = "true"
+///
+/// [obs]
+/// command = "/usr/bin/obs"
+/// args = ["--startvirtualcam"]
+/// window_size = [1280, 720]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct ManifestFile {
+    #[serde(flatten)]
+    apps: HashMap<String, AppManifestEntry>,
+}
+
+/// Live, reloadable view of the app manifest. `AppManager` resolves `Start`/
+/// `Switch` commands against this instead of trusting raw commands/args sent
+/// over MQTT, and `reload()` lets an operator edit the manifest on disk and
+/// have it picked up without restarting the process.
+pub struct AppRegistry {
+    path: PathBuf,
+    apps: RwLock<HashMap<String, AppManifestEntry>>,
+}
+
+impl AppRegistry {
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let apps = Self::read_manifest(&path)?;
+        Ok(Self {
+            path,
+            apps: RwLock::new(apps),
+        })
+    }
+
+    /// Missing manifest just means no apps are registered yet (fine for a
+    /// debug/idle-only setup); a malformed one is a hard error.
+    fn read_manifest(path: &Path) -> Result<HashMap<String, AppManifestEntry>> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                log::warn!("App manifest {} not found, starting with an empty registry", path.display());
+                return Ok(HashMap::new());
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to read app manifest at {}", path.display())),
+        };
+        let manifest: ManifestFile = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse app manifest at {}", path.display()))?;
+        Ok(manifest.apps)
+    }
+
+    /// Re-reads the manifest from disk and swaps it in atomically. Called from
+    /// the SIGHUP handler in `AppManager::run`.
+    pub fn reload(&self) -> Result<()> {
+        let apps = Self::read_manifest(&self.path)?;
+        log::info!("Reloaded app manifest from {} ({} apps)", self.path.display(), apps.len());
+        *self.apps.write().unwrap() = apps;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<AppManifestEntry> {
+        self.apps.read().unwrap().get(name).cloned()
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+use crate::modules::mqtt_handler::AppCommand;
+
+/// Where the control socket lives: `PROTOSUIT_ENGINE_SOCKET` if set (mirroring
+/// Alacritty's `ALACRITTY_SOCKET`), otherwise `$XDG_RUNTIME_DIR/protosuit-engine.sock`
+/// (falling back to `/tmp` if that isn't set either). Both `AppManager` and
+/// the `msg` CLI subcommand resolve the same default, so nothing needs to
+/// pass the other a path unless it's been overridden.
+pub fn socket_path() -> PathBuf {
+    if let Ok(path) = std::env::var("PROTOSUIT_ENGINE_SOCKET") {
+        return PathBuf::from(path);
+    }
+    let dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+    Path::new(&dir).join("protosuit-engine.sock")
+}
+
+/// Binds the control socket and feeds every command it receives into
+/// `command_tx` - the same queue MQTT feeds - so local tooling and keybinds
+/// keep working when the broker is unreachable. Each connection carries
+/// exactly one JSON-encoded `AppCommand`.
+pub fn spawn(command_tx: mpsc::Sender<AppCommand>) -> Result<tokio::task::JoinHandle<()>> {
+    let path = socket_path();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create control socket directory {}", parent.display()))?;
+    }
+    // A stale socket left behind by an unclean shutdown would otherwise make
+    // bind() fail with AddrInUse.
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove stale control socket at {}", path.display()))?;
+    }
+
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind control socket at {}", path.display()))?;
+    log::info!("Listening for control commands on {}", path.display());
+
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let command_tx = command_tx.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, command_tx).await {
+                            log::warn!("Control socket connection error: {}", e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Failed to accept control socket connection: {}", e),
+            }
+        }
+    }))
+}
+
+async fn handle_connection(mut stream: UnixStream, command_tx: mpsc::Sender<AppCommand>) -> Result<()> {
+    let mut buf = Vec::new();
+    stream
+        .read_to_end(&mut buf)
+        .await
+        .context("Failed to read control socket message")?;
+    let command: AppCommand =
+        serde_json::from_slice(&buf).context("Failed to parse control socket message")?;
+    command_tx
+        .send(command)
+        .await
+        .context("Failed to queue control socket command")?;
+    Ok(())
+}
+
+/// Connects to the control socket and sends a single serialized command -
+/// the client side used by `protosuit-engine msg`.
+pub async fn send(command: &AppCommand) -> Result<()> {
+    let path = socket_path();
+    let mut stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("Failed to connect to control socket at {}", path.display()))?;
+
+    let body = serde_json::to_vec(command).context("Failed to serialize command")?;
+    stream.write_all(&body).await.context("Failed to send command")?;
+    stream.shutdown().await.context("Failed to close control socket connection")?;
+    Ok(())
+}
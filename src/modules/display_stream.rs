@@ -0,0 +1,252 @@
+use anyhow::{Context, Result};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::modules::sdl_manager::{RenderFn, SDLManager};
+
+/// Chunk payload size, kept comfortably under a typical 1500-byte Ethernet MTU
+/// once the header and IP/UDP overhead are accounted for.
+const CHUNK_PAYLOAD_SIZE: usize = 1400;
+const HEADER_SIZE: usize = 14;
+
+/// Fixed-size header prefixed to every UDP chunk. A frame larger than one
+/// datagram is split across `chunk_count` chunks sharing `frame_index`, which
+/// the receiver uses to reassemble it.
+#[derive(Debug, Clone, Copy)]
+struct PacketHeader {
+    frame_index: u32,
+    width: u16,
+    height: u16,
+    chunk_index: u16,
+    chunk_count: u16,
+    payload_len: u16,
+}
+
+impl PacketHeader {
+    fn to_bytes(self) -> [u8; HEADER_SIZE] {
+        let mut buf = [0u8; HEADER_SIZE];
+        buf[0..4].copy_from_slice(&self.frame_index.to_be_bytes());
+        buf[4..6].copy_from_slice(&self.width.to_be_bytes());
+        buf[6..8].copy_from_slice(&self.height.to_be_bytes());
+        buf[8..10].copy_from_slice(&self.chunk_index.to_be_bytes());
+        buf[10..12].copy_from_slice(&self.chunk_count.to_be_bytes());
+        buf[12..14].copy_from_slice(&self.payload_len.to_be_bytes());
+        buf
+    }
+
+    fn from_bytes(buf: &[u8]) -> Option<Self> {
+        if buf.len() < HEADER_SIZE {
+            return None;
+        }
+        Some(Self {
+            frame_index: u32::from_be_bytes(buf[0..4].try_into().ok()?),
+            width: u16::from_be_bytes(buf[4..6].try_into().ok()?),
+            height: u16::from_be_bytes(buf[6..8].try_into().ok()?),
+            chunk_index: u16::from_be_bytes(buf[8..10].try_into().ok()?),
+            chunk_count: u16::from_be_bytes(buf[10..12].try_into().ok()?),
+            payload_len: u16::from_be_bytes(buf[12..14].try_into().ok()?),
+        })
+    }
+}
+
+/// Sends rendered frames to a remote display node over UDP, chunked to fit
+/// the MTU. Skips frames whose pixels are unchanged since the last send.
+pub struct FrameSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    next_frame_index: u32,
+    last_frame_hash: Option<u64>,
+}
+
+impl FrameSender {
+    pub fn new(target: SocketAddr) -> Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0").context("Failed to bind stream sender socket")?;
+        Ok(Self {
+            socket,
+            target,
+            next_frame_index: 0,
+            last_frame_hash: None,
+        })
+    }
+
+    /// `rgb` must be tightly packed RGB24 (`width * height * 3` bytes).
+    pub fn send_frame(&mut self, width: u16, height: u16, rgb: &[u8]) -> Result<()> {
+        let hash = fnv1a(rgb);
+        if self.last_frame_hash == Some(hash) {
+            return Ok(());
+        }
+        self.last_frame_hash = Some(hash);
+
+        let frame_index = self.next_frame_index;
+        self.next_frame_index = self.next_frame_index.wrapping_add(1);
+
+        let chunks: Vec<&[u8]> = rgb.chunks(CHUNK_PAYLOAD_SIZE).collect();
+        let chunk_count = chunks.len() as u16;
+
+        for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+            let header = PacketHeader {
+                frame_index,
+                width,
+                height,
+                chunk_index: chunk_index as u16,
+                chunk_count,
+                payload_len: chunk.len() as u16,
+            };
+
+            let mut packet = Vec::with_capacity(HEADER_SIZE + chunk.len());
+            packet.extend_from_slice(&header.to_bytes());
+            packet.extend_from_slice(chunk);
+
+            self.socket
+                .send_to(&packet, self.target)
+                .context("Failed to send frame chunk")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the canvas back with `read_pixels` and forwards it through `sender`.
+/// Meant to be called at the end of a scene's render closure, after
+/// `canvas.present()`.
+pub fn capture_and_send(canvas: &mut Canvas<Window>, sender: &Mutex<FrameSender>) -> Result<()> {
+    let (width, height) = canvas
+        .output_size()
+        .map_err(|e| anyhow::anyhow!("Failed to get canvas size: {}", e))?;
+
+    let rgb = canvas
+        .read_pixels(None, PixelFormatEnum::RGB24)
+        .map_err(|e| anyhow::anyhow!("Failed to read canvas pixels: {}", e))?;
+
+    sender
+        .lock()
+        .unwrap()
+        .send_frame(width as u16, height as u16, &rgb)
+}
+
+struct PartialFrame {
+    header: PacketHeader,
+    chunks: HashMap<u16, Vec<u8>>,
+}
+
+impl PartialFrame {
+    fn new(header: PacketHeader) -> Self {
+        Self { header, chunks: HashMap::new() }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.chunks.len() as u16 == self.header.chunk_count
+    }
+
+    fn assemble(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.chunks.values().map(Vec::len).sum());
+        for i in 0..self.header.chunk_count {
+            if let Some(chunk) = self.chunks.get(&i) {
+                out.extend_from_slice(chunk);
+            }
+        }
+        out
+    }
+}
+
+/// Listens for chunked frames and blits each reassembled one into a local
+/// window by id. Runs on its own thread since UDP reads here are blocking.
+pub struct FrameReceiver;
+
+impl FrameReceiver {
+    pub fn spawn(bind_addr: SocketAddr, sdl: Arc<SDLManager>, window_id: u32) -> Result<()> {
+        let socket = UdpSocket::bind(bind_addr).context("Failed to bind stream receiver socket")?;
+
+        thread::Builder::new()
+            .name("frame-stream-rx".to_string())
+            .spawn(move || Self::run(socket, sdl, window_id))
+            .context("Failed to spawn stream receiver thread")?;
+
+        Ok(())
+    }
+
+    fn run(socket: UdpSocket, sdl: Arc<SDLManager>, window_id: u32) {
+        let mut buf = [0u8; HEADER_SIZE + CHUNK_PAYLOAD_SIZE];
+        let mut current: Option<PartialFrame> = None;
+
+        loop {
+            let len = match socket.recv(&mut buf) {
+                Ok(len) => len,
+                Err(e) => {
+                    log::warn!("Stream receiver read error: {}", e);
+                    continue;
+                }
+            };
+
+            let Some(header) = PacketHeader::from_bytes(&buf[..len]) else {
+                continue;
+            };
+            let payload = &buf[HEADER_SIZE..len];
+
+            let frame = match &mut current {
+                Some(frame) if frame.header.frame_index == header.frame_index => frame,
+                // UDP gives no ordering guarantee, so a stray chunk from an
+                // older frame can arrive after a newer one has already
+                // started reassembling - compare with wraparound-aware
+                // ordering and only swap in the incoming frame if it's
+                // actually newer, otherwise drop the stray chunk.
+                Some(frame) if (header.frame_index.wrapping_sub(frame.header.frame_index) as i32) <= 0 => {
+                    continue;
+                }
+                _ => {
+                    current = Some(PartialFrame::new(header));
+                    current.as_mut().unwrap()
+                }
+            };
+            frame.chunks.insert(header.chunk_index, payload.to_vec());
+
+            if frame.is_complete() {
+                let rgb = frame.assemble();
+                let width = frame.header.width as u32;
+                let height = frame.header.height as u32;
+
+                let draw: RenderFn = Box::new(move |canvas: &mut Canvas<Window>| {
+                    if let Err(e) = Self::blit(canvas, width, height, &rgb) {
+                        log::error!("Failed to blit streamed frame: {}", e);
+                    }
+                });
+
+                if let Err(e) = sdl.render(window_id, draw) {
+                    log::error!("Failed to submit streamed frame: {}", e);
+                }
+                current = None;
+            }
+        }
+    }
+
+    fn blit(canvas: &mut Canvas<Window>, width: u32, height: u32, rgb: &[u8]) -> Result<()> {
+        let texture_creator = canvas.texture_creator();
+        let mut rgb = rgb.to_vec();
+        let surface = sdl2::surface::Surface::from_data(&mut rgb, width, height, width * 3, PixelFormatEnum::RGB24)
+            .map_err(|e| anyhow::anyhow!("Failed to build surface from streamed frame: {}", e))?;
+        let texture = texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| anyhow::anyhow!("Failed to create texture: {}", e))?;
+
+        canvas.clear();
+        canvas
+            .copy(&texture, None, None)
+            .map_err(|e| anyhow::anyhow!("Failed to copy streamed frame: {}", e))?;
+        canvas.present();
+        Ok(())
+    }
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in data {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+use futures_util::stream::StreamExt;
+use std::time::Duration;
+use tokio::sync::watch;
+use zbus::fdo::{DBusProxy, PropertiesProxy};
+use zbus::zvariant::Value;
+use zbus::{Connection, Proxy};
+
+const PREFERRED_PLAYER: &str = "org.mpris.MediaPlayer2.playerctld";
+const PLAYER_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const PLAYER_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Snapshot of the currently playing track, pushed by `MPRISHandler` whenever
+/// the session bus reports a change. `None` upstream means no MPRIS player is
+/// currently available, and `IdleScene` falls back to the clock in that case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: String,
+    pub playing: bool,
+}
+
+pub struct MPRISHandler;
+
+impl MPRISHandler {
+    /// Spawns the D-Bus polling/subscription loop on its own tokio task and
+    /// returns a `watch` receiver scenes can cheaply poll from the render tick
+    /// without blocking on D-Bus I/O themselves.
+    pub fn spawn() -> watch::Receiver<Option<NowPlaying>> {
+        let (tx, rx) = watch::channel(None);
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = Self::run(&tx).await {
+                    log::warn!("MPRIS session bus loop stopped: {}", e);
+                    let _ = tx.send(None);
+                }
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        });
+
+        rx
+    }
+
+    async fn run(tx: &watch::Sender<Option<NowPlaying>>) -> Result<()> {
+        let connection = Connection::session()
+            .await
+            .context("Failed to connect to D-Bus session bus")?;
+
+        loop {
+            match Self::find_player(&connection).await {
+                Some(name) => {
+                    log::debug!("Tracking MPRIS player {}", name);
+                    if let Err(e) = Self::watch_player(&connection, &name, tx).await {
+                        log::debug!("MPRIS player {} went away: {}", name, e);
+                    }
+                }
+                None => {
+                    let _ = tx.send(None);
+                }
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+
+    /// Prefers `playerctld` (which itself proxies "whichever player is most
+    /// recently active") and otherwise falls back to the first MPRIS name on
+    /// the bus.
+    async fn find_player(connection: &Connection) -> Option<String> {
+        let dbus = DBusProxy::new(connection).await.ok()?;
+        let names = dbus.list_names().await.ok()?;
+
+        if names.iter().any(|n| n.as_str() == PREFERRED_PLAYER) {
+            return Some(PREFERRED_PLAYER.to_string());
+        }
+
+        names
+            .into_iter()
+            .map(|n| n.to_string())
+            .find(|n| n.starts_with(PLAYER_PREFIX))
+    }
+
+    async fn watch_player(
+        connection: &Connection,
+        name: &str,
+        tx: &watch::Sender<Option<NowPlaying>>,
+    ) -> Result<()> {
+        let player = Proxy::new(connection, name, PLAYER_PATH, PLAYER_INTERFACE)
+            .await
+            .context("Failed to build MPRIS player proxy")?;
+
+        let _ = tx.send(Some(Self::read_state(&player).await?));
+
+        let properties = PropertiesProxy::new(connection, name, PLAYER_PATH)
+            .await
+            .context("Failed to build DBus.Properties proxy")?;
+        let mut changes = properties.receive_properties_changed().await?;
+
+        while let Some(signal) = changes.next().await {
+            let args = signal.args().context("Failed to parse PropertiesChanged signal")?;
+            if args.interface_name() != PLAYER_INTERFACE {
+                continue;
+            }
+            let _ = tx.send(Some(Self::read_state(&player).await?));
+        }
+
+        Ok(())
+    }
+
+    async fn read_state(player: &Proxy<'_>) -> Result<NowPlaying> {
+        let playback_status: String = player
+            .get_property("PlaybackStatus")
+            .await
+            .unwrap_or_else(|_| "Stopped".to_string());
+
+        let metadata: std::collections::HashMap<String, Value> =
+            player.get_property("Metadata").await.unwrap_or_default();
+
+        let title = metadata
+            .get("xesam:title")
+            .and_then(|v| v.downcast_ref::<str>().ok())
+            .unwrap_or("")
+            .to_string();
+
+        let artist = metadata
+            .get("xesam:artist")
+            .and_then(|v| v.downcast_ref::<zbus::zvariant::Array>().ok())
+            .and_then(|artists| artists.get(0).cloned())
+            .and_then(|v| v.downcast::<String>().ok())
+            .unwrap_or_default();
+
+        Ok(NowPlaying {
+            title,
+            artist,
+            playing: playback_status == "Playing",
+        })
+    }
+}
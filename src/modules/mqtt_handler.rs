@@ -1,24 +1,49 @@
 use anyhow::{Context, Result};
-use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+use rumqttc::{AsyncClient, Event, EventLoop, LastWill, MqttOptions, Packet, QoS};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
+use tokio::time::interval;
+
+use crate::modules::sdl_manager::SDLManager;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AppCommand {
-    Start { name: String, command: String, args: Vec<String> },
+    Start { name: String },
     Stop { name: String },
     Switch { name: String },
 }
 
+#[derive(Debug, Serialize)]
+struct Heartbeat {
+    sequence: u64,
+    session: u64,
+    uptime_secs: u64,
+    running_apps: Vec<String>,
+}
+
 pub struct MQTTHandler {
     client: AsyncClient,
     eventloop: EventLoop,
     command_tx: mpsc::Sender<AppCommand>,
     connection_status_tx: mpsc::Sender<bool>,
     pub(crate) shutdown_rx: oneshot::Receiver<()>,
+    sdl_manager: Arc<SDLManager>,
+    heartbeat_interval: Duration,
+    heartbeat_topic: String,
+    // Shared with the heartbeat task so the sequence keeps climbing across
+    // reconnects instead of resetting, and the session counter only moves on
+    // an actual reconnect - letting a controller tell a reconnect apart from
+    // a silent process restart.
+    sequence: Arc<AtomicU64>,
+    session: Arc<AtomicU64>,
+    started_at: Instant,
 }
 
+const ENGINE_STATUS_TOPIC: &str = "app/status/engine";
+
 impl MQTTHandler {
     pub fn new(
         broker: &str,
@@ -26,6 +51,9 @@ impl MQTTHandler {
         client_id: &str,
         command_tx: mpsc::Sender<AppCommand>,
         connection_status_tx: mpsc::Sender<bool>,
+        sdl_manager: Arc<SDLManager>,
+        heartbeat_interval: Duration,
+        heartbeat_topic: String,
     ) -> Result<Self> {
         let mut mqttopts = MqttOptions::new(client_id, broker, port);
         mqttopts
@@ -34,6 +62,7 @@ impl MQTTHandler {
             .set_connection_timeout(Duration::from_secs(10))
             .set_max_packet_size(100 * 1024)
             .set_pending_throttle(Duration::from_millis(100))
+            .set_last_will(LastWill::new(ENGINE_STATUS_TOPIC, "offline", QoS::AtLeastOnce, true))
             .set_reconnect_opts(rumqttc::ReconnectOptions::Exponential(
                 Duration::from_secs(1),    // Initial delay
                 Duration::from_secs(60),   // Max delay
@@ -49,6 +78,12 @@ impl MQTTHandler {
             command_tx,
             connection_status_tx,
             shutdown_rx,
+            sdl_manager,
+            heartbeat_interval,
+            heartbeat_topic,
+            sequence: Arc::new(AtomicU64::new(0)),
+            session: Arc::new(AtomicU64::new(0)),
+            started_at: Instant::now(),
         })
     }
 
@@ -59,6 +94,8 @@ impl MQTTHandler {
             .await
             .context("Failed to subscribe to topics")?;
 
+        let heartbeat_handle = self.spawn_heartbeat();
+
         let mut consecutive_errors = 0;
         let max_consecutive_errors = 3;
 
@@ -99,6 +136,14 @@ impl MQTTHandler {
                             self.connection_status_tx.send(true).await
                                 .context("Failed to send connection status")?;
 
+                            let session = self.session.fetch_add(1, Ordering::SeqCst) + 1;
+                            log::debug!("MQTT session counter now {}", session);
+
+                            self.client
+                                .publish(ENGINE_STATUS_TOPIC, QoS::AtLeastOnce, true, "online")
+                                .await
+                                .context("Failed to publish online status")?;
+
                             // Resubscribe to topics after reconnection
                             self.client.subscribe("app/+", QoS::AtLeastOnce).await
                                 .context("Failed to resubscribe to topics")?;
@@ -134,9 +179,53 @@ impl MQTTHandler {
                 }
             }
         }
+
+        heartbeat_handle.abort();
         Ok(())
     }
 
+    /// Spawns the periodic heartbeat publish. Runs independently of the event
+    /// loop above so a slow/blocked broker round-trip never delays it.
+    fn spawn_heartbeat(&self) -> tokio::task::JoinHandle<()> {
+        let client = self.client.clone();
+        let topic = self.heartbeat_topic.clone();
+        let period = self.heartbeat_interval;
+        let sequence = self.sequence.clone();
+        let session = self.session.clone();
+        let sdl_manager = self.sdl_manager.clone();
+        let started_at = self.started_at;
+
+        tokio::spawn(async move {
+            let mut ticker = interval(period);
+            loop {
+                ticker.tick().await;
+
+                let heartbeat = Heartbeat {
+                    sequence: sequence.fetch_add(1, Ordering::SeqCst),
+                    session: session.load(Ordering::SeqCst),
+                    uptime_secs: started_at.elapsed().as_secs(),
+                    running_apps: sdl_manager.get_running_apps(),
+                };
+
+                match serde_json::to_vec(&heartbeat) {
+                    Ok(body) => {
+                        if let Err(e) = client.publish(&topic, QoS::AtMostOnce, false, body).await {
+                            log::warn!("Failed to publish heartbeat: {}", e);
+                        }
+                    }
+                    Err(e) => log::warn!("Failed to serialize heartbeat: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Cheap clone of the underlying MQTT client, for subsystems (e.g.
+    /// ambient light) that need to publish from a task of their own rather
+    /// than routing everything through `MQTTHandler::start`'s event loop.
+    pub fn client(&self) -> AsyncClient {
+        self.client.clone()
+    }
+
     pub async fn publish_status(&self, app_name: &str, status: &str) -> Result<()> {
         let topic = format!("app/status/{}", app_name);
         self.client
@@ -1,18 +1,65 @@
 use anyhow::{Context, Result};
-use sdl2::video::Window;
-use std::sync::Arc;
 use dashmap::DashMap;
-use std::process::{Child, Command};
 use lazy_static::lazy_static;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 use sdl2::event::Event;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::HashMap;
+use std::process::{Child, Command};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 lazy_static! {
     pub static ref TTF_CONTEXT: Arc<sdl2::ttf::Sdl2TtfContext> = Arc::new(sdl2::ttf::init().unwrap());
 }
 
+/// A render callback submitted to the SDL thread. It receives the canvas for the
+/// window it targets and is responsible for calling `present()` itself.
+pub type RenderFn = Box<dyn FnMut(&mut Canvas<Window>) + Send>;
+
+/// Messages accepted by the dedicated SDL thread. Nothing that touches `Window`
+/// or `Canvas` crosses a thread boundary except through this enum, so callers
+/// never get a raw SDL handle back - only a `u32` window id, which is the
+/// window's real X11 XID (resolved via `raw_window_handle` at creation time)
+/// so it can be handed straight to `WindowManager` as well.
+enum SDLCommand {
+    CreateWindow {
+        title: String,
+        width: u32,
+        height: u32,
+        /// Scenes pinned to `transport = "stream"` still need a canvas to
+        /// render onto (and stream from), they just shouldn't ever be shown
+        /// on screen or touched by `WindowManager`.
+        visible: bool,
+        reply: Sender<Result<u32, String>>,
+    },
+    Render {
+        window_id: u32,
+        draw: RenderFn,
+    },
+    Destroy {
+        window_id: u32,
+    },
+    PumpEvents,
+    ReadPixels {
+        window_id: u32,
+        reply: Sender<Result<Vec<u8>, String>>,
+    },
+}
+
+struct RunningApp {
+    child: Child,
+    /// `None` for apps launched via `spawn_app` (`transport = "stream"` in
+    /// the manifest) - they have a process but no local SDL window.
+    window_id: Option<u32>,
+}
+
 pub struct SDLManager {
-    sdl_context: Arc<sdl2::Sdl>,
-    running_apps: DashMap<String, (Child, Window)>,
+    command_tx: Sender<SDLCommand>,
+    running_apps: DashMap<String, RunningApp>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -23,80 +70,249 @@ pub enum SDLError {
     NotFound(String),
     #[error("SDL error: {0}")]
     SDLError(String),
+    #[error("SDL thread is no longer running")]
+    ThreadGone,
 }
 
-impl SDLManager {
-    pub fn new() -> Result<Self> {
-        let sdl_context = sdl2::init()
-            .map_err(|e| SDLError::SDLError(e.to_string()))
-            .context("Failed to initialize SDL")?;
+const DEFAULT_WINDOW_WIDTH: u32 = 720;
+const DEFAULT_WINDOW_HEIGHT: u32 = 720;
 
-        // Hide cursor globally
-        sdl_context.mouse().show_cursor(false);
+impl SDLManager {
+    /// Spawns the dedicated SDL thread and returns a handle that talks to it over
+    /// an mpsc channel. SDL video must be created and serviced from the thread
+    /// that initialized it on most platforms, so `SDLManager` itself never touches
+    /// `Sdl`/`VideoSubsystem`/`Canvas` directly - it only enqueues commands.
+    pub fn new(quit_tx: tokio::sync::mpsc::UnboundedSender<()>) -> Result<Self> {
+        let (command_tx, command_rx) = mpsc::channel();
 
-        // Initialize TTF (will only happen once due to lazy_static)
-        let _ = &*TTF_CONTEXT;
+        thread::Builder::new()
+            .name("sdl-main".to_string())
+            .spawn(move || Self::run(command_rx, quit_tx))
+            .context("Failed to spawn SDL thread")?;
 
         Ok(Self {
-            sdl_context: Arc::new(sdl_context),
+            command_tx,
             running_apps: DashMap::new(),
         })
     }
 
-    pub fn launch_app(&self, app_name: &str, command: &str, args: &[&str]) -> Result<Option<sdl2::render::Canvas<Window>>> {
+    /// Body of the dedicated SDL thread. Owns the `Sdl` context, the
+    /// `VideoSubsystem`, and every live `Canvas<Window>`, keyed by the
+    /// window's X11 XID. Loops: drain queued commands, then pump SDL events, forwarding
+    /// `Quit` to `quit_tx` instead of calling `std::process::exit` directly.
+    fn run(command_rx: Receiver<SDLCommand>, quit_tx: tokio::sync::mpsc::UnboundedSender<()>) {
+        // Under `test-support`, force SDL's built-in headless driver so the
+        // whole window/canvas/render path - including `read_pixels` - works
+        // in CI without a real X server or GPU, without scenes needing to
+        // know they're being tested.
+        #[cfg(feature = "test-support")]
+        std::env::set_var("SDL_VIDEODRIVER", "dummy");
+
+        let sdl_context = match sdl2::init() {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                log::error!("SDL thread failed to initialize SDL: {}", e);
+                return;
+            }
+        };
+        sdl_context.mouse().show_cursor(false);
+
+        // Initialize TTF once; scenes load fonts lazily via the shared context.
+        let _ = &*TTF_CONTEXT;
+
+        let video_subsystem = match sdl_context.video() {
+            Ok(vs) => vs,
+            Err(e) => {
+                log::error!("SDL thread failed to get video subsystem: {}", e);
+                return;
+            }
+        };
+
+        let mut event_pump = match sdl_context.event_pump() {
+            Ok(pump) => pump,
+            Err(e) => {
+                log::error!("SDL thread failed to get event pump: {}", e);
+                return;
+            }
+        };
+
+        let mut canvases: HashMap<u32, Canvas<Window>> = HashMap::new();
+
+        'main: loop {
+            // Drain every queued command before touching the event queue so a
+            // burst of Render calls doesn't get interleaved with stale frames.
+            loop {
+                match command_rx.try_recv() {
+                    Ok(command) => Self::handle_command(command, &video_subsystem, &mut canvases),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => break 'main,
+                }
+            }
+
+            for event in event_pump.poll_iter() {
+                if let Event::Quit { .. } = event {
+                    log::info!("SDL quit event received, forwarding to shutdown path");
+                    let _ = quit_tx.send(());
+                }
+            }
+
+            // Fixed cadence; `PumpEvents` commands don't shorten this sleep,
+            // events are polled every tick regardless.
+            thread::sleep(Duration::from_millis(16));
+        }
+
+        log::info!("SDL thread exiting");
+    }
+
+    fn handle_command(
+        command: SDLCommand,
+        video_subsystem: &sdl2::VideoSubsystem,
+        canvases: &mut HashMap<u32, Canvas<Window>>,
+    ) {
+        match command {
+            SDLCommand::CreateWindow { title, width, height, visible, reply } => {
+                let mut builder = video_subsystem.window(&title, width, height);
+                builder.position_centered().borderless();
+                if !visible {
+                    builder.hidden();
+                }
+                let result = builder
+                    .build()
+                    .map_err(|e| e.to_string())
+                    .and_then(|window| {
+                        let mut canvas_builder = window.into_canvas();
+                        if visible {
+                            // Hidden windows never get mapped by the
+                            // compositor, so there's no vsync signal to wait
+                            // on - requesting it here could block `present()`
+                            // forever instead of just swapping immediately,
+                            // stalling the shared SDL thread for every scene.
+                            canvas_builder = canvas_builder.present_vsync();
+                        }
+                        canvas_builder.build().map_err(|e| e.to_string())
+                    })
+                    .map(|canvas| {
+                        // `window_manager` talks to the real X11 window (EWMH
+                        // ClientMessages, RandR placement), so the id we hand
+                        // back needs to be the XID the WM actually knows, not
+                        // SDL's own `SDL_GetWindowID()` counter - they're
+                        // different namespaces and neither is derivable from
+                        // the other. Fall back to the SDL id under backends
+                        // (test-support's dummy driver) that don't expose a
+                        // real Xlib handle, since `WindowManager` is a no-op
+                        // there anyway.
+                        let window_id = match canvas.window().raw_window_handle() {
+                            RawWindowHandle::Xlib(handle) => handle.window as u32,
+                            _ => canvas.window().id(),
+                        };
+                        canvases.insert(window_id, canvas);
+                        window_id
+                    });
+                let _ = reply.send(result);
+            }
+            SDLCommand::Render { window_id, mut draw } => {
+                if let Some(canvas) = canvases.get_mut(&window_id) {
+                    draw(canvas);
+                } else {
+                    log::warn!("Render requested for unknown window id {}", window_id);
+                }
+            }
+            SDLCommand::Destroy { window_id } => {
+                canvases.remove(&window_id);
+            }
+            SDLCommand::PumpEvents => {
+                // Handled by the unconditional poll_iter() below each loop tick.
+            }
+            SDLCommand::ReadPixels { window_id, reply } => {
+                let result = match canvases.get_mut(&window_id) {
+                    Some(canvas) => canvas
+                        .read_pixels(None, sdl2::pixels::PixelFormatEnum::RGB24)
+                        .map_err(|e| e.to_string()),
+                    None => Err(format!("ReadPixels requested for unknown window id {}", window_id)),
+                };
+                let _ = reply.send(result);
+            }
+        }
+    }
+
+    pub fn launch_app(
+        &self,
+        app_name: &str,
+        command: &str,
+        args: &[&str],
+        window_size: Option<(u32, u32)>,
+        visible: bool,
+    ) -> Result<u32> {
         if self.running_apps.contains_key(app_name) {
             return Err(SDLError::AlreadyRunning(app_name.to_string()).into());
         }
 
-        // Create SDL window for the application
-        let video_subsystem = self.sdl_context.video()
-            .map_err(|e| SDLError::SDLError(e.to_string()))?;
+        let (width, height) = window_size.unwrap_or((DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT));
+
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.command_tx
+            .send(SDLCommand::CreateWindow {
+                title: app_name.to_string(),
+                width,
+                height,
+                visible,
+                reply: reply_tx,
+            })
+            .map_err(|_| SDLError::ThreadGone)?;
+
+        let window_id = reply_rx
+            .recv()
+            .map_err(|_| SDLError::ThreadGone)?
+            .map_err(SDLError::SDLError)?;
+
+        log::debug!("Launching app: {} with command: {}", app_name, command);
+        let child = Command::new(command)
+            .args(args)
+            .spawn()
+            .context("Failed to spawn process")?;
+
+        self.running_apps
+            .insert(app_name.to_string(), RunningApp { child, window_id: Some(window_id) });
+
+        Ok(window_id)
+    }
 
-        // Add macOS-specific GL attributes
-        #[cfg(target_os = "macos")]
-        {
-            let gl_attr = video_subsystem.gl_attr();
-            gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
-            gl_attr.set_context_version(3, 2);
+    /// Spawns `app_name` without a local SDL window, for manifest entries
+    /// with `transport = "stream"` - the app still runs (and can still be
+    /// `stop`ped), it just never gets a window `WindowManager` could
+    /// focus/minimize/fullscreen.
+    pub fn spawn_app(&self, app_name: &str, command: &str, args: &[&str]) -> Result<()> {
+        if self.running_apps.contains_key(app_name) {
+            return Err(SDLError::AlreadyRunning(app_name.to_string()).into());
         }
 
-        let window = video_subsystem.window(app_name, 720, 720)
-            .position_centered()
-            .borderless()
-            // .opengl()
-            // .allow_highdpi()
-            // .resizable()
-            .build()
-            .context("Failed to create window")?;
-
-        // For the idle/debug displays, we don't actually launch a process
-        let child = if command == "true" {
-            log::debug!("Creating display canvas for app: {}", app_name);
-            let canvas = window.into_canvas()
-                .present_vsync()
-                .build()
-                .context("Failed to create canvas")?;
-
-            let window = canvas.window().clone();
-            let child = Command::new("true").spawn().context("Failed to spawn dummy process")?;
-            self.running_apps.insert(app_name.to_string(), (child, window));
-            return Ok(Some(canvas));
-        } else {
-            Command::new(command)
-                .args(args)
-                .spawn()
-                .context("Failed to spawn process")?
-        };
+        log::debug!("Launching windowless app: {} with command: {}", app_name, command);
+        let child = Command::new(command)
+            .args(args)
+            .spawn()
+            .context("Failed to spawn process")?;
+
+        self.running_apps
+            .insert(app_name.to_string(), RunningApp { child, window_id: None });
 
-        self.running_apps.insert(app_name.to_string(), (child, window));
-        Ok(None)
+        Ok(())
+    }
+
+    /// Submit a render closure for `window_id`. The closure runs on the SDL
+    /// thread and is expected to call `canvas.present()` itself.
+    pub fn render(&self, window_id: u32, draw: RenderFn) -> Result<()> {
+        self.command_tx
+            .send(SDLCommand::Render { window_id, draw })
+            .map_err(|_| SDLError::ThreadGone.into())
     }
 
     pub fn stop_app(&self, app_name: &str) -> Result<()> {
-        if let Some(mut entry) = self.running_apps.remove(app_name) {
-            let (ref mut child, _) = entry.1;
-            child.kill().context("Failed to kill process")?;
-            child.wait().context("Failed to wait for process")?;
+        if let Some((_, mut app)) = self.running_apps.remove(app_name) {
+            app.child.kill().context("Failed to kill process")?;
+            app.child.wait().context("Failed to wait for process")?;
+            if let Some(window_id) = app.window_id {
+                let _ = self.command_tx.send(SDLCommand::Destroy { window_id });
+            }
             Ok(())
         } else {
             Err(SDLError::NotFound(app_name.to_string()).into())
@@ -104,43 +320,46 @@ impl SDLManager {
     }
 
     pub fn get_window(&self, app_name: &str) -> Option<u32> {
-        self.running_apps.get(app_name).map(|entry| {
-            let window = &entry.value().1;
-            window.raw() as u32
-        })
+        self.running_apps.get(app_name).and_then(|entry| entry.value().window_id)
+    }
+
+    /// Reads back the whole canvas for `window_id` as packed RGB24 bytes.
+    /// Mainly useful under `test-support`, where it's the only way to assert
+    /// a scene actually drew what it claims to without a display to look at.
+    pub fn read_pixels(&self, window_id: u32) -> Result<Vec<u8>> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.command_tx
+            .send(SDLCommand::ReadPixels { window_id, reply: reply_tx })
+            .map_err(|_| SDLError::ThreadGone)?;
+
+        reply_rx
+            .recv()
+            .map_err(|_| SDLError::ThreadGone)?
+            .map_err(SDLError::SDLError)
+            .map_err(Into::into)
     }
 
     pub fn cleanup(&self) -> Result<()> {
         for mut entry in self.running_apps.iter_mut() {
-            let (ref mut child, _) = entry.value_mut();
-            child.kill().context("Failed to kill process")?;
-            child.wait().context("Failed to wait for process")?;
+            entry.child.kill().context("Failed to kill process")?;
+            entry.child.wait().context("Failed to wait for process")?;
+            if let Some(window_id) = entry.window_id {
+                let _ = self.command_tx.send(SDLCommand::Destroy { window_id });
+            }
         }
         self.running_apps.clear();
         Ok(())
     }
 
     pub fn get_running_apps(&self) -> Vec<String> {
-        self.running_apps.iter()
-            .map(|entry| entry.key().clone())
-            .collect()
+        self.running_apps.iter().map(|entry| entry.key().clone()).collect()
     }
 
+    /// Kept for call sites that used to drive SDL's event pump directly; now it
+    /// only enqueues a no-op command, since the SDL thread polls events on its
+    /// own fixed cadence anyway.
     pub fn pump_events(&self) {
-        let mut event_pump = self.sdl_context.event_pump()
-            .expect("Failed to get event pump");
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} => {
-                    log::info!("SDL quit event received");
-                    std::process::exit(0);
-                }
-                _ => {
-                    // Handle other events if needed
-                    log::debug!("SDL event: {:?}", event);
-                }
-            }
-        }
+        let _ = self.command_tx.send(SDLCommand::PumpEvents);
     }
 }
 
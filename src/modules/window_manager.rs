@@ -1,28 +1,99 @@
 use anyhow::Result;
+use bitflags::bitflags;
 
-#[cfg(all(unix, not(target_os = "macos")))]
+#[cfg(all(not(feature = "test-support"), unix, not(target_os = "macos")))]
 use {
     anyhow::Context,
+    dashmap::DashMap,
     x11rb::connection::Connection,
     x11rb::protocol::xproto::*,
+    std::os::unix::io::AsRawFd,
     std::sync::Arc,
 };
 
-#[cfg(all(unix, not(target_os = "macos")))]
+bitflags! {
+    /// Mirrors wezterm's window-state concept: the bits of EWMH/ICCCM state we
+    /// actually care about, tracked locally so scenes don't have to round-trip
+    /// to the X server to ask "am I fullscreen right now?".
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct WindowState: u8 {
+        const FULLSCREEN = 1 << 0;
+        const MAXIMIZED = 1 << 1;
+        const HIDDEN = 1 << 2;
+    }
+}
+
+/// A resize or state change observed on the X11 event stream for a window we
+/// manage, forwarded to `AppManager` so the active scene can recompute its
+/// layout instead of calling `output_size()` every render.
+#[derive(Debug, Clone, Copy)]
+pub struct ResizeEvent {
+    pub window_id: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A physical display output (RandR CRTC + output pair), as reported by
+/// `WindowManager::outputs`. A protogen head typically has two of these, one
+/// per eye panel - `AppManager` pins a scene to one by `name` so its window
+/// lands at the right spot instead of wherever the compositor happens to
+/// place it.
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub name: String,
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    /// Refresh rate in Hz, derived from the output's current mode line.
+    /// `0.0` if RandR didn't report a usable mode.
+    pub refresh_rate: f64,
+}
+
+#[cfg(all(not(feature = "test-support"), unix, not(target_os = "macos")))]
 mod unix {
     use super::*;
 
-    struct EWMHAtoms {
-        _NET_ACTIVE_WINDOW: Atom,
-        _NET_WM_STATE: Atom,
-        _NET_WM_STATE_HIDDEN: Atom,
-        WM_PROTOCOLS: Atom,
-        WM_DELETE_WINDOW: Atom,
+    x11rb::atom_manager! {
+        pub EWMHAtoms: EWMHAtomsCookie {
+            _NET_ACTIVE_WINDOW,
+            _NET_WM_STATE,
+            _NET_WM_STATE_FULLSCREEN,
+            _NET_WM_STATE_HIDDEN,
+            WM_CHANGE_STATE,
+            WM_PROTOCOLS,
+            WM_DELETE_WINDOW,
+        }
+    }
+
+    const ICONIC_STATE: u32 = 3;
+    const SOURCE_INDICATION_APPLICATION: u32 = 1;
+
+    /// Values for the `data[0]` field of a `_NET_WM_STATE` client message, per
+    /// the EWMH spec.
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) enum WmStateAction {
+        Remove = 0,
+        Add = 1,
+        Toggle = 2,
+    }
+
+    /// Wraps the XCB socket for `tokio::io::unix::AsyncFd` so the resize
+    /// watcher task can `.readable().await` on it instead of parking a
+    /// dedicated OS thread in a blocking read.
+    struct ConnFd(Arc<x11rb::xcb_ffi::XCBConnection>);
+
+    impl AsRawFd for ConnFd {
+        fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+            self.0.as_raw_fd()
+        }
     }
 
     pub struct WindowManager {
         conn: Arc<x11rb::xcb_ffi::XCBConnection>,
         root: Window,
+        atoms: EWMHAtoms,
+        window_states: DashMap<u32, WindowState>,
     }
 
     impl WindowManager {
@@ -32,79 +103,238 @@ mod unix {
             let setup = conn.setup();
             let root = setup.roots[screen_num].root;
 
-            Ok(Self { conn, root })
+            // Intern every atom we'll ever need once, up front, instead of on
+            // every minimize/close call.
+            let atoms = EWMHAtoms::new(&*conn)?
+                .reply()
+                .context("Failed to intern EWMH atoms")?;
+
+            Ok(Self {
+                conn,
+                root,
+                atoms,
+                window_states: DashMap::new(),
+            })
         }
 
-        fn get_atom(&self, name: &str) -> Result<Atom> {
-            Ok(self.conn.intern_atom(false, name.as_bytes())?
-                .reply()
-                .context("Failed to get atom")?
-                .atom)
+        /// Sends a spec-compliant `ClientMessageEvent` to the root window so a
+        /// real EWMH-compliant window manager acts on it, rather than poking
+        /// the target window's properties directly.
+        fn send_root_client_message(&self, window: Window, message_type: Atom, data: [u32; 5]) -> Result<()> {
+            let event = ClientMessageEvent::new(32, window, message_type, data);
+            self.conn.send_event(
+                false,
+                self.root,
+                EventMask::SUBSTRUCTURE_NOTIFY | EventMask::SUBSTRUCTURE_REDIRECT,
+                event,
+            )?;
+            self.conn.flush()?;
+            Ok(())
         }
 
-        fn get_ewmh_atoms(&self) -> Result<EWMHAtoms> {
-            Ok(EWMHAtoms {
-                _NET_ACTIVE_WINDOW: self.get_atom("_NET_ACTIVE_WINDOW")?,
-                _NET_WM_STATE: self.get_atom("_NET_WM_STATE")?,
-                _NET_WM_STATE_HIDDEN: self.get_atom("_NET_WM_STATE_HIDDEN")?,
-                WM_PROTOCOLS: self.get_atom("WM_PROTOCOLS")?,
-                WM_DELETE_WINDOW: self.get_atom("WM_DELETE_WINDOW")?,
-            })
+        pub(crate) fn set_wm_state(&self, window_id: u32, state_atom: Atom, action: WmStateAction) -> Result<()> {
+            let window = window_id as Window;
+            self.send_root_client_message(
+                window,
+                self.atoms._NET_WM_STATE,
+                [action as u32, state_atom, 0, SOURCE_INDICATION_APPLICATION, 0],
+            )
         }
 
+        /// Focuses and raises `window_id` via `_NET_ACTIVE_WINDOW`.
         pub fn focus_window(&self, window_id: u32) -> Result<()> {
             let window = window_id as Window;
-            self.conn.set_input_focus(InputFocus::PARENT, window, x11rb::CURRENT_TIME)?;
-            self.conn.flush()?;
-            Ok(())
+            self.send_root_client_message(
+                window,
+                self.atoms._NET_ACTIVE_WINDOW,
+                [SOURCE_INDICATION_APPLICATION, x11rb::CURRENT_TIME, 0, 0, 0],
+            )
         }
 
+        /// Iconifies `window_id` via `WM_CHANGE_STATE`, per ICCCM - setting
+        /// `_NET_WM_STATE_HIDDEN` directly only tells other clients the window
+        /// is hidden, it doesn't ask the window manager to actually do it.
         pub fn minimize_window(&self, window_id: u32) -> Result<()> {
             let window = window_id as Window;
-            let atom = self.conn.intern_atom(false, b"_NET_WM_STATE")?;
-            let atom_minimize = self.conn.intern_atom(false, b"_NET_WM_STATE_HIDDEN")?;
-
-            if let (Ok(atom_reply), Ok(atom_minimize_reply)) = (atom.reply(), atom_minimize.reply()) {
-                self.conn.change_property(
-                    PropMode::REPLACE,
-                    window,
-                    atom_reply.atom,
-                    AtomEnum::ATOM,
-                    32,
-                    1,
-                    &[atom_minimize_reply.atom],
-                )?;
-                self.conn.flush()?;
-            }
+            self.send_root_client_message(window, self.atoms.WM_CHANGE_STATE, [ICONIC_STATE, 0, 0, 0, 0])?;
+            self.window_states.entry(window_id).or_default().insert(WindowState::HIDDEN);
             Ok(())
         }
 
+        /// Toggles `_NET_WM_STATE_FULLSCREEN` via the root ClientMessage - the
+        /// kiosk mode a fursuit display runs in most of the time.
+        pub fn set_fullscreen(&self, window_id: u32, fullscreen: bool) -> Result<()> {
+            let action = if fullscreen { WmStateAction::Add } else { WmStateAction::Remove };
+            self.set_wm_state(window_id, self.atoms._NET_WM_STATE_FULLSCREEN, action)?;
+
+            let mut state = self.window_states.entry(window_id).or_default();
+            state.set(WindowState::FULLSCREEN, fullscreen);
+            Ok(())
+        }
+
+        /// Last known `WindowState` for `window_id`, as tracked locally from
+        /// the calls made through this `WindowManager`. State changes made by
+        /// the window manager or other clients are not observed.
+        pub fn window_state(&self, window_id: u32) -> WindowState {
+            self.window_states.get(&window_id).map(|s| *s).unwrap_or_default()
+        }
+
+        /// Subscribes `window_id` to `StructureNotify` so `ConfigureNotify`
+        /// events (resizes) show up on the event stream `spawn_resize_watcher`
+        /// reads from.
+        pub fn track_resize(&self, window_id: u32) -> Result<()> {
+            let window = window_id as Window;
+            self.conn
+                .change_window_attributes(window, &ChangeWindowAttributesAux::new().event_mask(EventMask::STRUCTURE_NOTIFY))?;
+            self.conn.flush()?;
+            Ok(())
+        }
+
+        /// Spawns a task that waits for the XCB connection's socket to become
+        /// readable and forwards `ConfigureNotify` events as `ResizeEvent`s,
+        /// so `AppManager` can thread them into `Scene::on_resize` without
+        /// polling every tick. Readiness only means "at least one event is
+        /// queued", so every wakeup drains the connection until it's dry.
+        pub fn spawn_resize_watcher(self: &Arc<Self>) -> tokio::sync::mpsc::UnboundedReceiver<ResizeEvent> {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            let conn = self.conn.clone();
+
+            tokio::spawn(async move {
+                let async_fd = match tokio::io::unix::AsyncFd::new(ConnFd(conn.clone())) {
+                    Ok(async_fd) => async_fd,
+                    Err(e) => {
+                        log::error!("Failed to register X11 connection for async polling: {}", e);
+                        return;
+                    }
+                };
+
+                loop {
+                    let mut guard = match async_fd.readable().await {
+                        Ok(guard) => guard,
+                        Err(e) => {
+                            log::error!("X11 connection poll failed: {}", e);
+                            break;
+                        }
+                    };
+
+                    loop {
+                        match conn.poll_for_event() {
+                            Ok(Some(x11rb::protocol::Event::ConfigureNotify(event))) => {
+                                let resize = ResizeEvent {
+                                    window_id: event.window,
+                                    width: event.width as u32,
+                                    height: event.height as u32,
+                                };
+                                if tx.send(resize).is_err() {
+                                    return;
+                                }
+                            }
+                            Ok(Some(_)) => continue,
+                            Ok(None) => break,
+                            Err(e) => {
+                                log::error!("X11 resize watcher lost the connection: {}", e);
+                                return;
+                            }
+                        }
+                    }
+
+                    guard.clear_ready();
+                }
+            });
+
+            rx
+        }
+
         pub fn close_window(&self, window_id: u32) -> Result<()> {
             let window = window_id as Window;
-            let wm_protocols = self.conn.intern_atom(false, b"WM_PROTOCOLS")?.reply()?;
-            let wm_delete_window = self.conn.intern_atom(false, b"WM_DELETE_WINDOW")?.reply()?;
 
             let event = ClientMessageEvent::new(
                 32,
                 window,
-                wm_protocols.atom,
-                [wm_delete_window.atom, 0, 0, 0, 0],
+                self.atoms.WM_PROTOCOLS,
+                [self.atoms.WM_DELETE_WINDOW, 0, 0, 0, 0],
             );
 
-            self.conn.send_event(
-                false,
+            self.conn.send_event(false, window, EventMask::NO_EVENT, event)?;
+            self.conn.flush()?;
+            Ok(())
+        }
+
+        /// Enumerates every active CRTC via RandR, reporting the geometry and
+        /// refresh rate of whatever output it's currently driving. Disabled
+        /// CRTCs (no mode set, nothing plugged in) are skipped.
+        pub fn outputs(&self) -> Result<Vec<Output>> {
+            use x11rb::protocol::randr::ConnectionExt as _;
+
+            let resources = self
+                .conn
+                .get_screen_resources_current(self.root)?
+                .reply()
+                .context("Failed to get RandR screen resources")?;
+
+            let mut outputs = Vec::new();
+            for &crtc in &resources.crtcs {
+                let crtc_info = self
+                    .conn
+                    .get_crtc_info(crtc, resources.config_timestamp)?
+                    .reply()
+                    .context("Failed to get RandR CRTC info")?;
+
+                let Some(&output) = crtc_info.outputs.first() else {
+                    continue; // Disabled CRTC - nothing driving it.
+                };
+
+                let output_info = self
+                    .conn
+                    .get_output_info(output, resources.config_timestamp)?
+                    .reply()
+                    .context("Failed to get RandR output info")?;
+
+                let refresh_rate = resources
+                    .modes
+                    .iter()
+                    .find(|mode| mode.id == crtc_info.mode)
+                    .map(Self::mode_refresh_rate)
+                    .unwrap_or(0.0);
+
+                outputs.push(Output {
+                    name: String::from_utf8_lossy(&output_info.name).into_owned(),
+                    x: crtc_info.x,
+                    y: crtc_info.y,
+                    width: crtc_info.width,
+                    height: crtc_info.height,
+                    refresh_rate,
+                });
+            }
+
+            Ok(outputs)
+        }
+
+        /// Refresh rate in Hz for a RandR mode line: dot clock divided by the
+        /// total (visible + blanking) pixel count per frame.
+        fn mode_refresh_rate(mode: &x11rb::protocol::randr::ModeInfo) -> f64 {
+            if mode.htotal == 0 || mode.vtotal == 0 {
+                return 0.0;
+            }
+            mode.dot_clock as f64 / (mode.htotal as f64 * mode.vtotal as f64)
+        }
+
+        /// Moves `window_id` to the top-left corner of an output, e.g. to pin
+        /// a scene to a specific eye panel instead of wherever the compositor
+        /// chose to place it.
+        pub fn position_window(&self, window_id: u32, x: i16, y: i16) -> Result<()> {
+            let window = window_id as Window;
+            self.conn.configure_window(
                 window,
-                EventMask::NO_EVENT,
-                event,
+                &ConfigureWindowAux::new().x(x as i32).y(y as i32),
             )?;
-
             self.conn.flush()?;
             Ok(())
         }
     }
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(not(feature = "test-support"), target_os = "macos"))]
 mod macos {
     use super::*;
 
@@ -130,11 +360,97 @@ mod macos {
             log::debug!("macOS close_window called");
             Ok(())
         }
+
+        pub fn set_fullscreen(&self, _window_id: u32, _fullscreen: bool) -> Result<()> {
+            log::debug!("macOS set_fullscreen called");
+            Ok(())
+        }
+
+        pub fn window_state(&self, _window_id: u32) -> WindowState {
+            WindowState::default()
+        }
+
+        pub fn track_resize(&self, _window_id: u32) -> Result<()> {
+            Ok(())
+        }
+
+        /// No X11 event stream to watch on macOS, so the channel simply never
+        /// yields anything.
+        pub fn spawn_resize_watcher(self: &std::sync::Arc<Self>) -> tokio::sync::mpsc::UnboundedReceiver<ResizeEvent> {
+            let (_tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            rx
+        }
+
+        /// No RandR on macOS; nothing to pin a scene to.
+        pub fn outputs(&self) -> Result<Vec<Output>> {
+            Ok(Vec::new())
+        }
+
+        pub fn position_window(&self, _window_id: u32, _x: i16, _y: i16) -> Result<()> {
+            Ok(())
+        }
     }
 }
 
-#[cfg(all(unix, not(target_os = "macos")))]
+/// No-op backend used under `cargo test --features test-support`: CI has no
+/// X server to talk to, so every call just succeeds and `spawn_resize_watcher`
+/// hands back a channel that never yields - the same shape as the macOS stub
+/// above, since both are standing in for a real window manager we can't reach.
+#[cfg(feature = "test-support")]
+mod test_backend {
+    use super::*;
+
+    pub struct WindowManager {}
+
+    impl WindowManager {
+        pub fn new() -> Result<Self> {
+            Ok(Self {})
+        }
+
+        pub fn focus_window(&self, _window_id: u32) -> Result<()> {
+            Ok(())
+        }
+
+        pub fn minimize_window(&self, _window_id: u32) -> Result<()> {
+            Ok(())
+        }
+
+        pub fn close_window(&self, _window_id: u32) -> Result<()> {
+            Ok(())
+        }
+
+        pub fn set_fullscreen(&self, _window_id: u32, _fullscreen: bool) -> Result<()> {
+            Ok(())
+        }
+
+        pub fn window_state(&self, _window_id: u32) -> WindowState {
+            WindowState::default()
+        }
+
+        pub fn track_resize(&self, _window_id: u32) -> Result<()> {
+            Ok(())
+        }
+
+        pub fn spawn_resize_watcher(self: &std::sync::Arc<Self>) -> tokio::sync::mpsc::UnboundedReceiver<ResizeEvent> {
+            let (_tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            rx
+        }
+
+        pub fn outputs(&self) -> Result<Vec<Output>> {
+            Ok(Vec::new())
+        }
+
+        pub fn position_window(&self, _window_id: u32, _x: i16, _y: i16) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "test-support")]
+pub use test_backend::WindowManager;
+
+#[cfg(all(not(feature = "test-support"), unix, not(target_os = "macos")))]
 pub use unix::WindowManager;
 
-#[cfg(target_os = "macos")]
+#[cfg(all(not(feature = "test-support"), target_os = "macos"))]
 pub use macos::WindowManager;
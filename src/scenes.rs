@@ -0,0 +1,9 @@
+pub mod debug_scene;
+pub mod idle_scene;
+pub mod registry;
+pub mod scene;
+
+pub use debug_scene::DebugScene;
+pub use idle_scene::IdleScene;
+pub use registry::SceneRegistry;
+pub use scene::Scene;
@@ -1,88 +1,123 @@
 use anyhow::{Context, Result};
 use local_ip_address::local_ip;
-use sdl2::{
-    pixels::Color,
-    rect::Rect,
-    render::{Canvas, TextureCreator},
-    ttf::Font,
-    video::{Window, WindowContext},
-};
+use sdl2::{pixels::Color, rect::Rect, render::Canvas, video::Window};
+use std::sync::{Arc, Mutex};
 use systemstat::{Platform, System};
-use std::sync::Once;
-use lazy_static::lazy_static;
-use std::sync::Arc;
 use hostname;
 
-static TTF_INIT: Once = Once::new();
-
-lazy_static! {
-    static ref TTF_CONTEXT: Arc<sdl2::ttf::Sdl2TtfContext> = Arc::new(sdl2::ttf::init().unwrap());
-}
+use crate::modules::ambient_light::AmbientLight;
+use crate::modules::display_stream::{self, FrameSender};
+use crate::modules::sdl_manager::{SDLManager, TTF_CONTEXT};
+use crate::scenes::scene::Scene;
 
 pub struct DebugScene {
-    canvas: Canvas<Window>,
-    texture_creator: TextureCreator<WindowContext>,
-    font: Font<'static, 'static>,
+    sdl: Arc<SDLManager>,
+    window_id: u32,
     system: System,
     mqtt_connected: bool,
     hostname: String,
+    stream_sender: Option<Arc<Mutex<FrameSender>>>,
+    ambient_light: Option<Arc<Mutex<AmbientLight>>>,
+    size: (u32, u32),
 }
 
 impl DebugScene {
-    pub fn new(canvas: Canvas<Window>) -> Result<Self> {
-        let texture_creator = canvas.texture_creator();
-
-        // Initialize TTF only once
-        TTF_INIT.call_once(|| {
-            sdl2::ttf::init().expect("Failed to initialize TTF");
-        });
-
-        let font_data = include_bytes!("../../assets/RobotoMono-Regular.ttf");
-        let rwops = sdl2::rwops::RWops::from_bytes(font_data)
-            .map_err(|e| anyhow::anyhow!("Failed to load font data: {}", e))?;
-
-        // Use the static TTF context
-        let font = TTF_CONTEXT.load_font_from_rwops(rwops, 24)
-            .map_err(|e| anyhow::anyhow!("Failed to load font: {}", e))?;
-
-        // Get hostname
+    pub fn new(
+        sdl: Arc<SDLManager>,
+        window_id: u32,
+        stream_sender: Option<Arc<Mutex<FrameSender>>>,
+        ambient_light: Option<Arc<Mutex<AmbientLight>>>,
+    ) -> Result<Self> {
         let hostname = hostname::get()
             .map(|h| h.to_string_lossy().to_string())
             .unwrap_or_else(|_| "unknown".to_string());
 
         Ok(Self {
-            canvas,
-            texture_creator,
-            font,
+            sdl,
+            window_id,
             system: System::new(),
             mqtt_connected: false,
             hostname,
+            stream_sender,
+            ambient_light,
+            size: (720, 720),
         })
     }
 
+    pub fn window_id(&self) -> u32 {
+        self.window_id
+    }
+
     pub fn set_mqtt_status(&mut self, connected: bool) {
         self.mqtt_connected = connected;
     }
 
+    /// Called when the X11 resize watcher reports a new size for our window,
+    /// so `draw_frame` doesn't need to call `output_size()` every tick.
+    pub fn on_resize(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+    }
+
     pub fn render(&mut self) -> Result<()> {
-        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
-        self.canvas.clear();
+        let hostname = self.hostname.clone();
+        let mqtt_connected = self.mqtt_connected;
+        let ip = local_ip().context("Failed to get local IP")?;
+        let uptime = self.system.uptime().context("Failed to get uptime")?;
+        let stream_sender = self.stream_sender.clone();
+        let ambient_light = self.ambient_light.clone();
+        let size = self.size;
+
+        let draw = Box::new(move |canvas: &mut Canvas<Window>| {
+            if let Err(e) = Self::draw_frame(canvas, &hostname, ip, uptime, mqtt_connected, size) {
+                log::error!("Failed to render debug scene: {}", e);
+            }
+            if let Some(sender) = &stream_sender {
+                if let Err(e) = display_stream::capture_and_send(canvas, sender) {
+                    log::warn!("Failed to stream debug scene frame: {}", e);
+                }
+            }
+            if let Some(ambient_light) = &ambient_light {
+                if let Err(e) = ambient_light.lock().unwrap().capture(canvas) {
+                    log::warn!("Failed to capture debug scene frame for ambient light: {}", e);
+                }
+            }
+        });
+
+        self.sdl.render(self.window_id, draw)
+    }
+
+    /// Runs on the SDL thread inside the render closure: loads the font fresh
+    /// from the shared TTF context and draws the debug overlay onto `canvas`.
+    fn draw_frame(
+        canvas: &mut Canvas<Window>,
+        hostname: &str,
+        ip: std::net::IpAddr,
+        uptime: std::time::Duration,
+        mqtt_connected: bool,
+        (width, height): (u32, u32),
+    ) -> Result<()> {
+        let texture_creator = canvas.texture_creator();
+
+        let font_data = include_bytes!("../../assets/RobotoMono-Regular.ttf");
+        let rwops = sdl2::rwops::RWops::from_bytes(font_data)
+            .map_err(|e| anyhow::anyhow!("Failed to load font data: {}", e))?;
+        let font = TTF_CONTEXT
+            .load_font_from_rwops(rwops, 24)
+            .map_err(|e| anyhow::anyhow!("Failed to load font: {}", e))?;
+
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
 
-        let (width, height) = self.canvas.output_size()
-            .map_err(|e| anyhow::anyhow!("Failed to get canvas size: {}", e))?;
         let center_x = width as i32 / 2;
         let center_y = height as i32 / 2;
 
-        // Get system information
-        let ip = local_ip().context("Failed to get local IP")?;
-        let uptime = self.system.uptime().context("Failed to get uptime")?;
-        let mqtt_status = if self.mqtt_connected { "Connected" } else { "Disconnected" };
+        let mqtt_status = if mqtt_connected { "Connected" } else { "Disconnected" };
 
-        // Render text lines
         let lines = vec![
-            format!("Hostname: {}", self.hostname),
+            format!("Hostname: {}", hostname),
             format!("IP Address: {}", ip),
-            format!("Uptime: {}h {}m {}s",
+            format!(
+                "Uptime: {}h {}m {}s",
                 uptime.as_secs() / 3600,
                 (uptime.as_secs() % 3600) / 60,
                 uptime.as_secs() % 60
@@ -95,11 +130,12 @@ impl DebugScene {
         let start_y = center_y - (total_height / 2);
 
         for (i, line) in lines.iter().enumerate() {
-            let surface = self.font.render(line)
+            let surface = font
+                .render(line)
                 .blended(Color::RGB(255, 255, 255))
                 .map_err(|e| anyhow::anyhow!("Failed to render text: {}", e))?;
 
-            let texture = self.texture_creator
+            let texture = texture_creator
                 .create_texture_from_surface(&surface)
                 .map_err(|e| anyhow::anyhow!("Failed to create texture: {}", e))?;
 
@@ -110,11 +146,52 @@ impl DebugScene {
                 surface.height(),
             );
 
-            self.canvas.copy(&texture, None, Some(text_rect))
+            canvas
+                .copy(&texture, None, Some(text_rect))
                 .map_err(|e| anyhow::anyhow!("Failed to copy texture: {}", e))?;
         }
 
-        self.canvas.present();
+        canvas.present();
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl Scene for DebugScene {
+    fn render(&mut self) -> Result<()> {
+        DebugScene::render(self)
+    }
+
+    fn set_mqtt_status(&mut self, connected: bool) {
+        DebugScene::set_mqtt_status(self, connected)
+    }
+
+    fn window_id(&self) -> u32 {
+        DebugScene::window_id(self)
+    }
+
+    fn on_resize(&mut self, width: u32, height: u32) {
+        DebugScene::on_resize(self, width, height)
+    }
+}
+
+#[cfg(all(test, feature = "test-support"))]
+mod tests {
+    use super::*;
+    use crate::modules::sdl_manager::SDLManager;
+
+    #[test]
+    fn render_draws_non_blank_pixels() {
+        let (quit_tx, _quit_rx) = tokio::sync::mpsc::unbounded_channel();
+        let sdl = Arc::new(SDLManager::new(quit_tx).expect("SDL thread should start under the dummy driver"));
+        let window_id = sdl
+            .launch_app("Protosuit Debug", "true", &[], None, true)
+            .expect("launch_app should succeed");
+
+        let mut scene = DebugScene::new(sdl.clone(), window_id, None, None).expect("scene should construct");
+        scene.set_mqtt_status(true);
+        scene.render().expect("render should succeed");
+
+        let pixels = sdl.read_pixels(window_id).expect("read_pixels should succeed");
+        assert!(pixels.iter().any(|&byte| byte != 0), "expected the debug overlay to draw something other than a blank canvas");
+    }
+}
@@ -1,57 +1,134 @@
 use anyhow::Result;
-use sdl2::{
-    pixels::Color,
-    rect::Rect,
-    render::{Canvas, TextureCreator},
-    ttf::Font,
-    video::{Window, WindowContext},
-};
 use chrono::Local;
-use crate::modules::sdl_manager::TTF_CONTEXT;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::{pixels::Color, rect::Rect, render::Canvas, video::Window};
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+use crate::modules::ambient_light::AmbientLight;
+use crate::modules::display_stream::{self, FrameSender};
+use crate::modules::mpris_handler::NowPlaying;
+use crate::modules::sdl_manager::{SDLManager, TTF_CONTEXT};
+use crate::scenes::scene::Scene;
+
+const CANVAS_WIDTH: i32 = 720;
+const SCROLL_STEP: i32 = 4;
+const SCROLL_GAP: i32 = 60;
 
 pub struct IdleScene {
-    canvas: Canvas<Window>,
-    texture_creator: TextureCreator<WindowContext>,
-    font: Font<'static, 'static>,
+    sdl: Arc<SDLManager>,
+    window_id: u32,
+    now_playing_rx: watch::Receiver<Option<NowPlaying>>,
+    scroll_offset: i32,
+    stream_sender: Option<Arc<Mutex<FrameSender>>>,
+    ambient_light: Option<Arc<Mutex<AmbientLight>>>,
+    size: (u32, u32),
 }
 
 impl IdleScene {
-    pub fn new(canvas: Canvas<Window>) -> Result<Self> {
+    pub fn new(
+        sdl: Arc<SDLManager>,
+        window_id: u32,
+        now_playing_rx: watch::Receiver<Option<NowPlaying>>,
+        stream_sender: Option<Arc<Mutex<FrameSender>>>,
+        ambient_light: Option<Arc<Mutex<AmbientLight>>>,
+    ) -> Result<Self> {
+        Ok(Self {
+            sdl,
+            window_id,
+            now_playing_rx,
+            scroll_offset: 0,
+            stream_sender,
+            ambient_light,
+            size: (CANVAS_WIDTH as u32, CANVAS_WIDTH as u32),
+        })
+    }
+
+    pub fn window_id(&self) -> u32 {
+        self.window_id
+    }
+
+    /// Called when the X11 resize watcher reports a new size for our window,
+    /// so `draw_frame` doesn't need to call `output_size()` every tick.
+    pub fn on_resize(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+    }
+
+    pub fn render(&mut self) -> Result<()> {
+        let now_playing = self.now_playing_rx.borrow().clone();
+        self.scroll_offset = self.scroll_offset.wrapping_add(SCROLL_STEP);
+        let scroll_offset = self.scroll_offset;
+        let stream_sender = self.stream_sender.clone();
+        let ambient_light = self.ambient_light.clone();
+        let size = self.size;
+
+        let draw = Box::new(move |canvas: &mut Canvas<Window>| {
+            if let Err(e) = Self::draw_frame(canvas, now_playing.as_ref(), scroll_offset, size) {
+                log::error!("Failed to render idle scene: {}", e);
+            }
+            if let Some(sender) = &stream_sender {
+                if let Err(e) = display_stream::capture_and_send(canvas, sender) {
+                    log::warn!("Failed to stream idle scene frame: {}", e);
+                }
+            }
+            if let Some(ambient_light) = &ambient_light {
+                if let Err(e) = ambient_light.lock().unwrap().capture(canvas) {
+                    log::warn!("Failed to capture idle scene frame for ambient light: {}", e);
+                }
+            }
+        });
+
+        self.sdl.render(self.window_id, draw)
+    }
+
+    /// Runs on the SDL thread inside the render closure.
+    fn draw_frame(
+        canvas: &mut Canvas<Window>,
+        now_playing: Option<&NowPlaying>,
+        scroll_offset: i32,
+        (width, height): (u32, u32),
+    ) -> Result<()> {
         let texture_creator = canvas.texture_creator();
 
         let font_data = include_bytes!("../../assets/RobotoMono-Regular.ttf");
         let rwops = sdl2::rwops::RWops::from_bytes(font_data)
             .map_err(|e| anyhow::anyhow!("Failed to load font data: {}", e))?;
-
-        let font = TTF_CONTEXT.load_font_from_rwops(rwops, 24)
+        let font = TTF_CONTEXT
+            .load_font_from_rwops(rwops, 24)
             .map_err(|e| anyhow::anyhow!("Failed to load font: {}", e))?;
 
-        Ok(Self {
-            canvas,
-            texture_creator,
-            font,
-        })
-    }
-
-    pub fn render(&mut self) -> Result<()> {
-        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
-        self.canvas.clear();
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
 
-        let (width, height) = self.canvas.output_size()
-            .map_err(|e| anyhow::anyhow!("Failed to get canvas size: {}", e))?;
         let center_x = width as i32 / 2;
         let center_y = height as i32 / 2;
 
-        // Get current date and time
+        match now_playing {
+            Some(now_playing) if !now_playing.title.is_empty() => {
+                Self::draw_now_playing(canvas, &texture_creator, &font, now_playing, center_x, center_y, scroll_offset)?;
+            }
+            _ => Self::draw_clock(canvas, &texture_creator, &font, center_x, center_y)?,
+        }
+
+        canvas.present();
+        Ok(())
+    }
+
+    fn draw_clock(
+        canvas: &mut Canvas<Window>,
+        texture_creator: &sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+        font: &sdl2::ttf::Font,
+        center_x: i32,
+        center_y: i32,
+    ) -> Result<()> {
         let now = Local::now();
         let date_time = now.format("%Y-%m-%d %H:%M:%S").to_string();
 
-        // Render date and time
-        let surface = self.font.render(&date_time)
+        let surface = font
+            .render(&date_time)
             .blended(Color::RGB(255, 255, 255))
             .map_err(|e| anyhow::anyhow!("Failed to render text: {}", e))?;
-
-        let texture = self.texture_creator
+        let texture = texture_creator
             .create_texture_from_surface(&surface)
             .map_err(|e| anyhow::anyhow!("Failed to create texture: {}", e))?;
 
@@ -62,10 +139,87 @@ impl IdleScene {
             surface.height(),
         );
 
-        self.canvas.copy(&texture, None, Some(text_rect))
+        canvas
+            .copy(&texture, None, Some(text_rect))
             .map_err(|e| anyhow::anyhow!("Failed to copy texture: {}", e))?;
+        Ok(())
+    }
+
+    fn draw_now_playing(
+        canvas: &mut Canvas<Window>,
+        texture_creator: &sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+        font: &sdl2::ttf::Font,
+        now_playing: &NowPlaying,
+        center_x: i32,
+        center_y: i32,
+        scroll_offset: i32,
+    ) -> Result<()> {
+        let text = if now_playing.artist.is_empty() {
+            now_playing.title.clone()
+        } else {
+            format!("{} - {}", now_playing.title, now_playing.artist)
+        };
+
+        let surface = font
+            .render(&text)
+            .blended(Color::RGB(255, 255, 255))
+            .map_err(|e| anyhow::anyhow!("Failed to render text: {}", e))?;
+        let texture = texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| anyhow::anyhow!("Failed to create texture: {}", e))?;
+
+        let text_width = surface.width() as i32;
+        let text_height = surface.height() as i32;
+        let y = center_y - (text_height / 2);
+
+        if text_width > CANVAS_WIDTH {
+            // Marquee: draw two copies back to back and scroll them leftwards,
+            // wrapping once a full period (text + gap) has passed.
+            let period = text_width + SCROLL_GAP;
+            let x = CANVAS_WIDTH - (scroll_offset % period);
 
-        self.canvas.present();
+            canvas
+                .copy(&texture, None, Some(Rect::new(x, y, text_width as u32, text_height as u32)))
+                .map_err(|e| anyhow::anyhow!("Failed to copy texture: {}", e))?;
+            canvas
+                .copy(&texture, None, Some(Rect::new(x - period, y, text_width as u32, text_height as u32)))
+                .map_err(|e| anyhow::anyhow!("Failed to copy texture: {}", e))?;
+        } else {
+            let text_rect = Rect::new(center_x - (text_width / 2), y, text_width as u32, text_height as u32);
+            canvas
+                .copy(&texture, None, Some(text_rect))
+                .map_err(|e| anyhow::anyhow!("Failed to copy texture: {}", e))?;
+        }
+
+        Self::draw_play_pause_glyph(canvas, now_playing.playing, center_x as i16, (y + text_height + 20) as i16);
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Small geometric glyph so we don't need a second font/icon asset: two
+    /// bars for "paused", a triangle for "playing".
+    fn draw_play_pause_glyph(canvas: &mut Canvas<Window>, playing: bool, center_x: i16, y: i16) {
+        let color = Color::RGB(200, 200, 200);
+
+        if playing {
+            let _ = canvas.filled_trigon(center_x - 8, y, center_x - 8, y + 20, center_x + 10, y + 10, color);
+        } else {
+            canvas.set_draw_color(color);
+            let _ = canvas.fill_rect(Rect::new((center_x - 8) as i32, y as i32, 6, 20));
+            let _ = canvas.fill_rect(Rect::new((center_x + 4) as i32, y as i32, 6, 20));
+        }
+    }
+}
+
+impl Scene for IdleScene {
+    fn render(&mut self) -> Result<()> {
+        IdleScene::render(self)
+    }
+
+    fn window_id(&self) -> u32 {
+        IdleScene::window_id(self)
+    }
+
+    fn on_resize(&mut self, width: u32, height: u32) {
+        IdleScene::on_resize(self, width, height)
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::modules::ambient_light::AmbientLight;
+use crate::modules::display_stream::FrameSender;
+use crate::modules::sdl_manager::SDLManager;
+use crate::scenes::scene::Scene;
+
+type SceneFactory = Box<
+    dyn Fn(
+            Arc<SDLManager>,
+            u32,
+            Option<Arc<Mutex<FrameSender>>>,
+            Option<Arc<Mutex<AmbientLight>>>,
+        ) -> Result<Box<dyn Scene>>
+        + Send
+        + Sync,
+>;
+
+struct SceneEntry {
+    title: &'static str,
+    factory: SceneFactory,
+}
+
+/// Maps a scene name (as used in `AppCommand::Switch` and the default-scene
+/// env var) to the window title it launches under and the closure that
+/// builds it. Populated once in `AppManager::new`; `handle_switch` looks
+/// names up here instead of matching on a hardcoded list, so registering a
+/// new scene doesn't touch the run loop at all.
+#[derive(Default)]
+pub struct SceneRegistry {
+    entries: HashMap<String, SceneEntry>,
+}
+
+impl SceneRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        title: &'static str,
+        factory: impl Fn(
+                Arc<SDLManager>,
+                u32,
+                Option<Arc<Mutex<FrameSender>>>,
+                Option<Arc<Mutex<AmbientLight>>>,
+            ) -> Result<Box<dyn Scene>>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.entries.insert(name.to_string(), SceneEntry { title, factory: Box::new(factory) });
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Window title to launch `name` under, if it's registered.
+    pub fn title(&self, name: &str) -> Option<&'static str> {
+        self.entries.get(name).map(|entry| entry.title)
+    }
+
+    pub fn construct(
+        &self,
+        name: &str,
+        sdl: Arc<SDLManager>,
+        window_id: u32,
+        stream_sender: Option<Arc<Mutex<FrameSender>>>,
+        ambient_light: Option<Arc<Mutex<AmbientLight>>>,
+    ) -> Result<Box<dyn Scene>> {
+        let entry = self
+            .entries
+            .get(name)
+            .with_context(|| format!("No scene registered for '{}'", name))?;
+        (entry.factory)(sdl, window_id, stream_sender, ambient_light)
+    }
+}
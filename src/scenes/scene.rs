@@ -0,0 +1,23 @@
+use anyhow::Result;
+
+/// Common interface `AppManager`'s run loop dispatches through, so adding a
+/// new scene is a [`SceneRegistry`](crate::scenes::SceneRegistry) entry
+/// instead of a new arm in every match over scene names.
+pub trait Scene: Send {
+    /// Renders one frame. Runs on the SDL thread under the hood, same as the
+    /// concrete scenes' own `render`.
+    fn render(&mut self) -> Result<()>;
+
+    /// Called whenever the MQTT connection status changes. Scenes that don't
+    /// surface it (e.g. the idle clock) can ignore it via this default.
+    fn set_mqtt_status(&mut self, _connected: bool) {}
+
+    /// The X11 window this scene owns, so resize events can be routed to it.
+    /// Every scene has one - it's the canvas it renders (and streams) onto -
+    /// even a `transport = "stream"` scene, whose window is just never shown
+    /// or handed to `WindowManager`.
+    fn window_id(&self) -> u32;
+
+    /// Called when the X11 resize watcher reports a new size for our window.
+    fn on_resize(&mut self, width: u32, height: u32);
+}